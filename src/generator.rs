@@ -1,16 +1,23 @@
+pub(crate) mod anchors;
+pub(crate) mod constants;
+pub(crate) mod dependencies;
+pub(crate) mod filter;
 pub(crate) mod library;
+pub(crate) mod links;
 pub(crate) mod options;
 pub(crate) mod render;
+pub(crate) mod timings;
 pub(crate) mod toc;
 
 // -------------------------------------------------------------------------------------------------
 
-use std::{fs::*, io::Write, path::Path};
+use std::{fs::*, io::Write, path::Path, time::Instant};
 
 use crate::{
     error::Error,
     generator::{
         library::Library,
+        links::check_links,
         options::{Options, OutputOrder},
         toc::{replace_toc_in_file, TocEntry},
     },
@@ -24,6 +31,8 @@ use crate::{
 /// Resulting markdown files are generated and written to the output path as specified
 /// by the options.
 pub fn generate_docs(options: &Options) -> Result<(), Error> {
+    let options = &options.merge_deprecated();
+
     // validate options
     if !Path::exists(&options.library) {
         return Err(Error::Options(format!(
@@ -37,15 +46,55 @@ pub fn generate_docs(options: &Options) -> Result<(), Error> {
             options.output.as_path().to_string_lossy(),
         )));
     }
-    if options.order == OutputOrder::ByClass && options.namespace.is_empty() {
+    if options.order == OutputOrder::ByClass && options.namespaces.is_empty() {
         return Err(Error::Options(
-            "the order by-class option requires a namespace to be set too".to_string(),
+            "the order by-class option requires at least one namespace to be set too".to_string(),
         ));
     }
+    if options.namespaces.iter().any(String::is_empty) {
+        return Err(Error::Options("namespaces must not be empty".to_string()));
+    }
+    let std_modules = options.std_modules();
+    if let Some(module) = options.std_docs.iter().find(|m| !std_modules.contains(m)) {
+        return Err(Error::Options(format!(
+            "std docs module `{}` is not one of the std modules: `{}`",
+            module,
+            std_modules.join(", ")
+        )));
+    }
 
     // parse API and create docs
     let lib = Library::from_path(&options.library, options)?;
+    write_docs(&lib, options)
+}
+
+// render the docs of the given library and write them to the output path
+fn write_docs(lib: &Library, options: &Options) -> Result<(), Error> {
+    let mut timings = lib.timings.clone();
+    let start = Instant::now();
     let docs = lib.export_docs(options);
+    let toc_entries = docs
+        .iter()
+        .map(|(name, _)| TocEntry::from(name, options))
+        .collect::<Vec<_>>();
+    let toc_links = toc_entries
+        .iter()
+        .map(|entry| entry.link.clone())
+        .collect::<Vec<_>>();
+    timings.rendering = start.elapsed();
+
+    // print all links which point to missing pages or anchors
+    let start = Instant::now();
+    let broken_links = check_links(&docs, &toc_links);
+    if !broken_links.is_empty() {
+        println!("broken links:");
+        for link in broken_links {
+            println!("  \x1b[33m{}\x1b[0m", link);
+        }
+    }
+    timings.link_check = start.elapsed();
+
+    let start = Instant::now();
 
     // clear previously generated API doc files (except README.md)
     let api_path = options.output.clone().join("API");
@@ -66,15 +115,12 @@ pub fn generate_docs(options: &Options) -> Result<(), Error> {
         }
     }
 
-    // write docs to files and keep track of the TOC
-    let mut toc_links = vec![];
-    for (name, content) in &docs {
-        let toc_entry = TocEntry::from(name, options);
-        toc_links.push(toc_entry.link);
+    // write docs to files
+    for ((_, content), toc_entry) in docs.iter().zip(toc_entries) {
         let dir_path = api_path.clone().join(toc_entry.file_path.clone());
         let file_path = dir_path.clone().join(toc_entry.file_name + ".md");
         if !dir_path.exists() {
-            create_dir(dir_path)?;
+            create_dir_all(dir_path)?;
         }
         println!("Creating '{}'", file_path.to_string_lossy());
         let mut file = File::create(file_path)?;
@@ -87,5 +133,109 @@ pub fn generate_docs(options: &Options) -> Result<(), Error> {
         println!("Updating TOC at: '{}'", summary_file.to_string_lossy());
         replace_toc_in_file(&summary_file, &toc_links)?;
     }
+    timings.writing = start.elapsed();
+
+    if options.timings {
+        println!("Timings:\n{}", timings);
+    }
     Ok(())
 }
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    use tempdir::TempDir;
+    use url::Url;
+
+    use super::*;
+    use crate::{
+        generator::{constants::ConstantRules, filter::SymbolFilter},
+        parser::json::JsonDoc,
+    };
+
+    // contents of all files in the given directory and its sub directories, by relative path
+    fn read_files(dir: &Path) -> BTreeMap<PathBuf, String> {
+        let mut files = BTreeMap::new();
+        let mut dirs = vec![dir.to_path_buf()];
+        while let Some(current) = dirs.pop() {
+            for path in read_dir(current)
+                .unwrap()
+                .flatten()
+                .map(|entry| entry.path())
+            {
+                if path.is_dir() {
+                    dirs.push(path);
+                } else {
+                    let content = read_to_string(&path).unwrap();
+                    files.insert(path.strip_prefix(dir).unwrap().to_path_buf(), content);
+                }
+            }
+        }
+        files
+    }
+
+    // the sample book in test/src gets generated from the LuaLS `--doc` export of
+    // test/definitions in test/doc.json, with `--order=by-class --namespace=acme`. file urls
+    // in the export are relative to `file:///definitions/`. run with `UPDATE_SAMPLE_BOOK=1` to
+    // update the sample book.
+    #[test]
+    fn sample_book() {
+        let test_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("test");
+        let library_path = test_path.join("definitions").canonicalize().unwrap();
+        let library_url = Url::from_directory_path(&library_path).unwrap();
+        let json = read_to_string(test_path.join("doc.json"))
+            .unwrap()
+            .replace("file:///definitions/", library_url.as_str());
+        let definitions = JsonDoc::prepare(
+            &library_path,
+            None,
+            &[],
+            serde_json::from_str(&json).unwrap(),
+        );
+
+        let book_path = test_path.join("src");
+        let temp_dir = TempDir::new("sample_book").unwrap();
+        let update = std::env::var_os("UPDATE_SAMPLE_BOOK").is_some();
+        let output = if update {
+            book_path.clone()
+        } else {
+            copy(
+                book_path.join("SUMMARY.md"),
+                temp_dir.path().join("SUMMARY.md"),
+            )
+            .unwrap();
+            temp_dir.path().to_path_buf()
+        };
+        let options = Options {
+            library: library_path.clone(),
+            output: output.clone(),
+            order: OutputOrder::ByClass,
+            namespaces: vec!["acme".to_string()],
+            ..Default::default()
+        };
+        let filter = SymbolFilter::from_options(&options).unwrap();
+        let constant_rules = ConstantRules::from_options(&options).unwrap();
+        let library = Library::from_definitions(
+            &library_path,
+            &definitions,
+            &filter,
+            &constant_rules,
+            &options,
+        )
+        .unwrap();
+        write_docs(&library, &options).unwrap();
+
+        assert_eq!(
+            read_files(&output.join("API")),
+            read_files(&book_path.join("API")),
+            "sample book is outdated: run the test with `UPDATE_SAMPLE_BOOK=1` to update it"
+        );
+        assert_eq!(
+            read_to_string(output.join("SUMMARY.md")).unwrap(),
+            read_to_string(book_path.join("SUMMARY.md")).unwrap()
+        );
+    }
+}
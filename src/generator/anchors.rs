@@ -0,0 +1,261 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::parser::types::Class;
+
+// -------------------------------------------------------------------------------------------------
+
+/// Something on a doc page which gets an anchor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Target {
+    /// a class or struct, by class name
+    Class(String),
+    /// a field, constant, enum or operator, by class and member name
+    Member(String, String),
+    /// a function or constructor, by class and function name
+    Function(String, String),
+    /// an alias, by alias name
+    Alias(String),
+}
+
+impl Target {
+    pub fn class(name: &str) -> Self {
+        Self::Class(name.to_string())
+    }
+
+    pub fn member(class_name: &str, member: &str) -> Self {
+        Self::Member(class_name.to_string(), member.to_string())
+    }
+
+    pub fn function(class_name: &str, function: &str) -> Self {
+        Self::Function(class_name.to_string(), function.to_string())
+    }
+
+    pub fn alias(name: &str) -> Self {
+        Self::Alias(name.to_string())
+    }
+
+    /// anchor of the target, before it got made unique within its page. members are qualified
+    /// with their class name, so they don't collide with members of other classes that get
+    /// rendered into the same page. the `-fn` suffix keeps functions apart from fields with the
+    /// same name, so both can be linked to.
+    pub fn id(&self) -> String {
+        match self {
+            Self::Class(name) | Self::Alias(name) => slug(name),
+            Self::Member(class_name, member) => format!("{}--{}", slug(class_name), slug(member)),
+            Self::Function(class_name, function) => {
+                format!("{}--{}-fn", slug(class_name), slug(function))
+            }
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Unique anchors of all targets on a single doc page.
+///
+/// Anchors get assigned in the order in which targets are added. The first target with a
+/// given id uses the id as anchor, others get `-1`, `-2`, ... appended, as mdbook does for
+/// heading ids.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PageAnchors {
+    anchors: HashMap<Target, String>,
+    used: HashSet<String>,
+}
+
+impl PageAnchors {
+    /// assign an anchor to the given target, unless it already has one.
+    pub fn add(&mut self, target: Target) {
+        if self.anchors.contains_key(&target) {
+            return;
+        }
+        let id = target.id();
+        let mut anchor = id.clone();
+        let mut count = 0;
+        while self.used.contains(&anchor) {
+            count += 1;
+            anchor = format!("{}-{}", id, count);
+        }
+        self.used.insert(anchor.clone());
+        self.anchors.insert(target, anchor);
+    }
+
+    /// assign anchors to the given class and its members, in the order they get rendered.
+    pub fn add_class(&mut self, class: &Class) {
+        let name = &class.name;
+        self.add(Target::class(name));
+        for e in &class.enums {
+            self.add(Target::member(
+                name,
+                Class::get_end(&e.name).unwrap_or(&e.name),
+            ));
+        }
+        for v in &class.constants {
+            self.add(Target::member(name, v.name.as_deref().unwrap_or_default()));
+        }
+        for f in &class.constructors {
+            self.add(Target::function(name, f.name.as_deref().unwrap_or("fun")));
+        }
+        for v in &class.fields {
+            self.add(Target::member(name, v.name.as_deref().unwrap_or_default()));
+        }
+        for o in &class.operators {
+            self.add(Target::member(name, &format!("__{}", o.name)));
+        }
+        for f in &class.functions {
+            self.add(Target::function(name, f.name.as_deref().unwrap_or("fun")));
+        }
+    }
+
+    pub fn get(&self, target: &Target) -> Option<&str> {
+        self.anchors.get(target).map(String::as_str)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Anchors of all doc pages, by page path (relative to the API folder), along with the path of
+/// the page which gets rendered.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Anchors<'a> {
+    pub pages: &'a HashMap<String, PageAnchors>,
+    pub page: &'a str,
+}
+
+impl<'a> Anchors<'a> {
+    /// the same anchors, for rendering the page with the given path
+    pub fn for_page(&self, page: &'a str) -> Self {
+        Self {
+            pages: self.pages,
+            page,
+        }
+    }
+
+    /// anchor of the given target on the page that gets rendered
+    pub fn get(&self, target: &Target) -> Option<&'a str> {
+        self.get_on(self.page, target)
+    }
+
+    /// anchor of the given target on the page with the given path
+    pub fn get_on(&self, page: &str, target: &Target) -> Option<&'a str> {
+        self.pages.get(page).and_then(|anchors| anchors.get(target))
+    }
+
+    /// anchor of the given target on the page that gets rendered, for targets which get
+    /// defined in the page.
+    pub fn anchor(&self, target: &Target) -> String {
+        self.get(target)
+            .map(String::from)
+            .unwrap_or_else(|| target.id())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Anchor id of the given text, following mdbook's rules for heading ids: alphanumeric
+/// characters, `_` and `-` are kept and ASCII lowercased, whitespace gets replaced with `-`,
+/// everything else dropped.
+pub(crate) fn slug(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| {
+            if c.is_alphanumeric() || c == '_' || c == '-' {
+                Some(c.to_ascii_lowercase())
+            } else if c.is_whitespace() {
+                Some('-')
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::types::{Function, Kind, LuaKind, Scope, Var};
+
+    #[test]
+    fn slugs() {
+        // same as mdbook's `normalize_id`
+        assert_eq!(slug("acme.SomeClass"), "acmesomeclass");
+        assert_eq!(slug("Some Heading_1"), "some-heading_1");
+        assert_eq!(slug("require(\"acme-utils\")"), "requireacme-utils");
+        // mdbook lowercases ASCII characters only
+        assert_eq!(slug("Ärger"), "Ärger");
+    }
+
+    #[test]
+    fn unique_anchors() {
+        let mut anchors = PageAnchors::default();
+        anchors.add(Target::class("acme.Foo"));
+        anchors.add(Target::class("acmeFoo"));
+        anchors.add(Target::alias("acme.Foo"));
+        // targets keep the anchor they got first
+        anchors.add(Target::class("acme.Foo"));
+        // ids which look like suffixed ones don't collide with them either
+        anchors.add(Target::alias("acmefoo-1"));
+        assert_eq!(anchors.get(&Target::class("acme.Foo")), Some("acmefoo"));
+        assert_eq!(anchors.get(&Target::class("acmeFoo")), Some("acmefoo-1"));
+        assert_eq!(anchors.get(&Target::alias("acme.Foo")), Some("acmefoo-2"));
+        assert_eq!(
+            anchors.get(&Target::alias("acmefoo-1")),
+            Some("acmefoo-1-1")
+        );
+        assert_eq!(anchors.get(&Target::alias("acmeFoo")), None);
+
+        // class names with `-` may produce member-like ids: they still get unique anchors
+        let mut anchors = PageAnchors::default();
+        anchors.add(Target::member("a-", "b"));
+        anchors.add(Target::member("a", "-b"));
+        assert_eq!(anchors.get(&Target::member("a-", "b")), Some("a---b"));
+        assert_eq!(anchors.get(&Target::member("a", "-b")), Some("a---b-1"));
+    }
+
+    #[test]
+    fn class_anchors() {
+        let class = Class {
+            fields: vec![
+                Var::new("run", Kind::Lua(LuaKind::Boolean)),
+                Var::new("Run", Kind::Lua(LuaKind::Boolean)),
+            ],
+            functions: vec![Function::new("run")],
+            ..Class::new("acme.Foo", Scope::Global)
+        };
+        let mut anchors = PageAnchors::default();
+        anchors.add_class(&class);
+        assert_eq!(anchors.get(&Target::class("acme.Foo")), Some("acmefoo"));
+        assert_eq!(
+            anchors.get(&Target::member("acme.Foo", "run")),
+            Some("acmefoo--run")
+        );
+        assert_eq!(
+            anchors.get(&Target::member("acme.Foo", "Run")),
+            Some("acmefoo--run-1")
+        );
+        assert_eq!(
+            anchors.get(&Target::function("acme.Foo", "run")),
+            Some("acmefoo--run-fn")
+        );
+
+        let pages = HashMap::from([("acme/Foo".to_string(), anchors)]);
+        let anchors = Anchors {
+            pages: &pages,
+            page: "acme/Foo",
+        };
+        assert_eq!(
+            anchors.get_on("acme/Foo", &Target::class("acme.Foo")),
+            Some("acmefoo")
+        );
+        assert_eq!(
+            anchors.for_page("acme/Bar").get(&Target::class("acme.Foo")),
+            None
+        );
+        assert_eq!(
+            anchors
+                .for_page("acme/Bar")
+                .anchor(&Target::class("acme.Foo")),
+            "acmefoo"
+        );
+    }
+}
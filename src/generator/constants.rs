@@ -0,0 +1,159 @@
+use std::fmt;
+
+use regex::Regex;
+
+use crate::{
+    error::Error,
+    generator::{filter::DescriptionTag, options::Options},
+    parser::types::{Kind, Var},
+};
+
+// -------------------------------------------------------------------------------------------------
+
+/// The rule that classified a field as constant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConstantReason {
+    /// The field name matches the constant name pattern.
+    Name,
+    /// The field's `@type` is a literal value.
+    Literal,
+    /// The field's description contains the constant tag.
+    Tag,
+}
+
+impl fmt::Display for ConstantReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name => write!(f, "name pattern"),
+            Self::Literal => write!(f, "literal type"),
+            Self::Tag => write!(f, "constant tag"),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Configurable rules to detect which class fields should be documented as constants.
+#[derive(Debug, Clone)]
+pub(crate) struct ConstantRules {
+    name_pattern: Option<Regex>,
+    literal_types: bool,
+    tag: Option<DescriptionTag>,
+}
+
+impl ConstantRules {
+    pub fn from_options(options: &Options) -> Result<Self, Error> {
+        let name_pattern = if options.constant_pattern.is_empty() {
+            None
+        } else {
+            Some(Regex::new(&options.constant_pattern).map_err(|err| {
+                Error::Options(format!(
+                    "invalid constant pattern `{}`: {}",
+                    options.constant_pattern, err
+                ))
+            })?)
+        };
+        Ok(Self {
+            name_pattern,
+            literal_types: !options.no_literal_constants,
+            tag: DescriptionTag::from_option(&options.constant_tag)?,
+        })
+    }
+
+    /// Returns the reason why the given field is a constant, or None if it's a property.
+    pub fn classify(&self, var: &Var) -> Option<ConstantReason> {
+        if self
+            .tag
+            .as_ref()
+            .is_some_and(|tag| var.desc.as_ref().is_some_and(|desc| tag.is_in(desc)))
+        {
+            Some(ConstantReason::Tag)
+        } else if self.literal_types && matches!(var.kind, Kind::Literal(_, _)) {
+            Some(ConstantReason::Literal)
+        } else if self
+            .name_pattern
+            .as_ref()
+            .is_some_and(|re| var.name.as_ref().is_some_and(|name| re.is_match(name)))
+        {
+            Some(ConstantReason::Name)
+        } else {
+            None
+        }
+    }
+
+    /// Removes the constant tag from the given description.
+    pub fn strip_tag(&self, desc: &str) -> String {
+        match &self.tag {
+            Some(tag) => tag.strip(desc),
+            None => desc.to_string(),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::types::LuaKind;
+
+    fn field(name: &str, kind: Kind, desc: Option<&str>) -> Var {
+        Var {
+            desc: desc.map(|d| d.to_string()),
+            ..Var::new(name, kind)
+        }
+    }
+
+    #[test]
+    fn classify() {
+        let rules = ConstantRules::from_options(&Options::default()).unwrap();
+        let number = Kind::Lua(LuaKind::Number);
+        let literal = Kind::Literal(Box::new(LuaKind::Integer), "1".to_string());
+        assert_eq!(
+            rules.classify(&field("MAX_VALUE", number.clone(), None)),
+            Some(ConstantReason::Name)
+        );
+        assert_eq!(
+            rules.classify(&field("max_value", literal.clone(), None)),
+            Some(ConstantReason::Literal)
+        );
+        assert_eq!(
+            rules.classify(&field("MAX_VALUE", literal, Some("Max @constant"))),
+            Some(ConstantReason::Tag)
+        );
+        assert_eq!(
+            rules.classify(&field("max_value", number.clone(), None)),
+            None
+        );
+        assert_eq!(
+            rules.classify(&field("MaxValue", number, Some("Not @constants"))),
+            None
+        );
+        assert_eq!(rules.strip_tag("Max value @constant"), "Max value");
+    }
+
+    #[test]
+    fn disabled_rules() {
+        let options = Options {
+            constant_pattern: String::new(),
+            constant_tag: String::new(),
+            no_literal_constants: true,
+            ..Default::default()
+        };
+        let rules = ConstantRules::from_options(&options).unwrap();
+        let literal = Kind::Literal(Box::new(LuaKind::Integer), "1".to_string());
+        assert_eq!(
+            rules.classify(&field("MAX_VALUE", literal, Some("Max @constant"))),
+            None
+        );
+        assert_eq!(rules.strip_tag("Max @constant"), "Max @constant");
+        let options = Options {
+            constant_pattern: "[".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            ConstantRules::from_options(&options),
+            Err(Error::Options(_))
+        ));
+    }
+}
@@ -0,0 +1,385 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::parser::types::{Alias, Class, Scope, SymbolTable};
+
+// -------------------------------------------------------------------------------------------------
+
+/// A local class (struct) or alias, which gets inlined into the pages that use it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Node {
+    Struct(String),
+    Alias(String),
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Struct(name) | Self::Alias(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Dependency graph of all structs and aliases in a library.
+///
+/// The graph is built once per library and then queried for every rendered page, to find all
+/// structs and aliases a page needs to inline. References to structs or aliases which are not
+/// part of the library (e.g. because they got excluded) are skipped.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TypeDependencies {
+    edges: HashMap<Node, Vec<Node>>,
+}
+
+impl TypeDependencies {
+    pub fn new(
+        classes: &HashMap<String, Class>,
+        aliases: &HashMap<String, Alias>,
+        symbols: &SymbolTable,
+    ) -> Self {
+        let mut edges = HashMap::new();
+        for class in classes.values().filter(|c| c.scope == Scope::Local) {
+            edges.insert(
+                Node::Struct(class.name.clone()),
+                Self::sorted_nodes(
+                    class.collect_local_class_types(symbols),
+                    class.collect_alias_types(symbols),
+                ),
+            );
+        }
+        for alias in aliases.values() {
+            edges.insert(
+                Node::Alias(alias.name.clone()),
+                Self::sorted_nodes(
+                    alias.kind.collect_local_class_types(symbols),
+                    alias.kind.collect_alias_types(symbols),
+                ),
+            );
+        }
+        Self { edges }
+    }
+
+    /// names of all structs and aliases the given class refers to, directly or indirectly via
+    /// other structs and aliases.
+    pub fn local_types(
+        &self,
+        class: &Class,
+        symbols: &SymbolTable,
+    ) -> (HashSet<String>, HashSet<String>) {
+        let start = Self::sorted_nodes(
+            class.collect_local_class_types(symbols),
+            class.collect_alias_types(symbols),
+        );
+        let mut struct_names = HashSet::new();
+        let mut alias_names = HashSet::new();
+        for node in self.reachable(start, |_| true) {
+            match node {
+                Node::Struct(name) => struct_names.insert(name),
+                Node::Alias(name) => alias_names.insert(name),
+            };
+        }
+        (struct_names, alias_names)
+    }
+
+    /// names of all aliases the given class refers to, directly or indirectly via other aliases.
+    pub fn local_aliases(&self, class: &Class, symbols: &SymbolTable) -> HashSet<String> {
+        let start = Self::sorted_nodes(HashSet::new(), class.collect_alias_types(symbols));
+        self.reachable(start, |node| matches!(node, Node::Alias(_)))
+            .into_iter()
+            .map(|node| node.to_string())
+            .collect()
+    }
+
+    /// all reference cycles between structs and aliases, as sorted lists of names.
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        let mut nodes = self.edges.keys().collect::<Vec<_>>();
+        nodes.sort();
+        let mut tarjan = Tarjan {
+            edges: &self.edges,
+            index: 0,
+            indices: HashMap::new(),
+            low_links: HashMap::new(),
+            stack: vec![],
+            on_stack: HashSet::new(),
+            components: vec![],
+        };
+        for node in nodes {
+            if !tarjan.indices.contains_key(node) {
+                tarjan.visit(node);
+            }
+        }
+        let mut cycles = tarjan
+            .components
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || self
+                        .edges
+                        .get(component[0])
+                        .is_some_and(|targets| targets.contains(component[0]))
+            })
+            .map(|component| {
+                let mut names = component
+                    .into_iter()
+                    .map(|node| node.to_string())
+                    .collect::<Vec<_>>();
+                names.sort();
+                names
+            })
+            .collect::<Vec<_>>();
+        cycles.sort();
+        cycles
+    }
+
+    // collect all nodes which can be reached from the given start nodes, following only edges
+    // to nodes which pass the given filter. missing nodes are included, but not followed.
+    fn reachable(&self, start: Vec<Node>, follow: impl Fn(&Node) -> bool) -> HashSet<Node> {
+        let mut visited = HashSet::new();
+        let mut worklist = start;
+        while let Some(node) = worklist.pop() {
+            if visited.contains(&node) {
+                continue;
+            }
+            if let Some(targets) = self.edges.get(&node) {
+                worklist.extend(
+                    targets
+                        .iter()
+                        .filter(|target| !visited.contains(*target) && follow(target))
+                        .cloned(),
+                );
+            }
+            visited.insert(node);
+        }
+        visited
+    }
+
+    fn sorted_nodes(struct_names: HashSet<String>, alias_names: HashSet<String>) -> Vec<Node> {
+        let mut nodes = struct_names
+            .into_iter()
+            .map(Node::Struct)
+            .chain(alias_names.into_iter().map(Node::Alias))
+            .collect::<Vec<_>>();
+        nodes.sort();
+        nodes
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+// Tarjan's strongly connected components algorithm, used to detect reference cycles
+struct Tarjan<'a> {
+    edges: &'a HashMap<Node, Vec<Node>>,
+    index: usize,
+    indices: HashMap<&'a Node, usize>,
+    low_links: HashMap<&'a Node, usize>,
+    stack: Vec<&'a Node>,
+    on_stack: HashSet<&'a Node>,
+    components: Vec<Vec<&'a Node>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn visit(&mut self, node: &'a Node) {
+        self.indices.insert(node, self.index);
+        self.low_links.insert(node, self.index);
+        self.index += 1;
+        self.stack.push(node);
+        self.on_stack.insert(node);
+
+        let edges = self.edges;
+        // targets which are not part of the graph can't be part of a cycle
+        let targets = edges.get(node).into_iter().flatten();
+        for target in targets.filter_map(|t| edges.get_key_value(t).map(|(k, _)| k)) {
+            if !self.indices.contains_key(target) {
+                self.visit(target);
+                let low_link = self.low_links[node].min(self.low_links[target]);
+                self.low_links.insert(node, low_link);
+            } else if self.on_stack.contains(target) {
+                let low_link = self.low_links[node].min(self.indices[target]);
+                self.low_links.insert(node, low_link);
+            }
+        }
+
+        if self.low_links[node] == self.indices[node] {
+            let mut component = vec![];
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(member);
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::types::{Kind, LuaKind, Symbol, SymbolType, Var};
+
+    // name, scope and fields of a test class
+    type TestClass<'a> = (&'a str, Scope, Vec<(&'a str, Kind)>);
+
+    // a library of classes and aliases, with resolved field and alias kinds
+    struct TestLibrary {
+        classes: HashMap<String, Class>,
+        aliases: HashMap<String, Alias>,
+        symbols: SymbolTable,
+    }
+
+    impl TestLibrary {
+        fn new(classes: &[TestClass], aliases: &[(&str, Kind)]) -> Self {
+            let mut symbols = SymbolTable::default();
+            for (name, scope, _) in classes {
+                symbols.insert(Symbol {
+                    symbol_type: SymbolType::Class,
+                    name: name.to_string(),
+                    file: None,
+                    scope: scope.clone(),
+                });
+            }
+            for (name, _) in aliases {
+                symbols.insert(Symbol {
+                    symbol_type: SymbolType::Alias,
+                    name: name.to_string(),
+                    file: None,
+                    scope: Scope::Global,
+                });
+            }
+            let classes = classes
+                .iter()
+                .map(|(name, scope, fields)| {
+                    let fields = fields
+                        .iter()
+                        .map(|(field, kind)| Var::new(field, symbols.resolve_kind(kind)))
+                        .collect();
+                    let class = Class {
+                        fields,
+                        ..Class::new(name, scope.clone())
+                    };
+                    (name.to_string(), class)
+                })
+                .collect();
+            let aliases = aliases
+                .iter()
+                .map(|(name, kind)| {
+                    (
+                        name.to_string(),
+                        Alias::new(name, symbols.resolve_kind(kind)),
+                    )
+                })
+                .collect();
+            Self {
+                classes,
+                aliases,
+                symbols,
+            }
+        }
+
+        fn dependencies(&self) -> TypeDependencies {
+            TypeDependencies::new(&self.classes, &self.aliases, &self.symbols)
+        }
+    }
+
+    fn unresolved(name: &str) -> Kind {
+        Kind::Unresolved(name.to_string())
+    }
+
+    fn names(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn self_cycle() {
+        let library = TestLibrary::new(
+            &[(
+                "Node",
+                Scope::Local,
+                vec![("next", Kind::Nullable(Box::new(unresolved("Node"))))],
+            )],
+            &[],
+        );
+        assert_eq!(
+            library.dependencies().cycles(),
+            vec![vec!["Node".to_string()]]
+        );
+    }
+
+    #[test]
+    fn alias_cycle() {
+        let library = TestLibrary::new(
+            &[
+                (
+                    "Tree",
+                    Scope::Local,
+                    vec![("children", unresolved("Trees"))],
+                ),
+                ("Leaf", Scope::Local, vec![("parent", unresolved("Tree"))]),
+            ],
+            &[("Trees", Kind::Array(Box::new(unresolved("Tree"))))],
+        );
+        assert_eq!(
+            library.dependencies().cycles(),
+            vec![vec!["Tree".to_string(), "Trees".to_string()]]
+        );
+    }
+
+    #[test]
+    fn reachable_types() {
+        let library = TestLibrary::new(
+            &[
+                (
+                    "acme.Thing",
+                    Scope::Global,
+                    vec![
+                        (
+                            "map",
+                            Kind::Table(
+                                Box::new(Kind::Lua(LuaKind::String)),
+                                Box::new(unresolved("Entry")),
+                            ),
+                        ),
+                        (
+                            "value",
+                            Kind::Enum(vec![unresolved("Value"), Kind::Lua(LuaKind::Integer)]),
+                        ),
+                    ],
+                ),
+                ("Entry", Scope::Local, vec![("mode", unresolved("Mode"))]),
+                ("Value", Scope::Local, vec![]),
+                ("Unused", Scope::Local, vec![]),
+            ],
+            &[
+                ("Mode", Kind::Enum(vec![unresolved("Modes")])),
+                (
+                    "Modes",
+                    Kind::Enum(vec![
+                        Kind::Literal(Box::new(LuaKind::String), "a".to_string()),
+                        Kind::Literal(Box::new(LuaKind::String), "b".to_string()),
+                    ]),
+                ),
+            ],
+        );
+        let dependencies = library.dependencies();
+        let thing = &library.classes["acme.Thing"];
+        let (structs, aliases) = dependencies.local_types(thing, &library.symbols);
+        assert_eq!(structs, names(&["Entry", "Value"]));
+        assert_eq!(aliases, names(&["Mode", "Modes"]));
+        // aliases are only followed through other aliases
+        assert!(dependencies
+            .local_aliases(thing, &library.symbols)
+            .is_empty());
+        let entry = &library.classes["Entry"];
+        assert_eq!(
+            dependencies.local_aliases(entry, &library.symbols),
+            names(&["Mode", "Modes"])
+        );
+        assert!(dependencies.cycles().is_empty());
+    }
+}
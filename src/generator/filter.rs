@@ -0,0 +1,314 @@
+use regex::Regex;
+
+use crate::{error::Error, generator::options::Options, parser::types::Visibility};
+
+// -------------------------------------------------------------------------------------------------
+
+/// The kinds of symbols a filter pattern can be restricted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SymbolKind {
+    Class,
+    Function,
+    Field,
+    Enum,
+    Alias,
+}
+
+impl SymbolKind {
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "class" => Some(Self::Class),
+            "function" => Some(Self::Function),
+            "field" => Some(Self::Field),
+            "enum" => Some(Self::Enum),
+            "alias" => Some(Self::Alias),
+            _ => None,
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A single include or exclude pattern, optionally restricted to a symbol kind.
+///
+/// Patterns are written as `[kind:][re:]pattern`, e.g. `_*`, `class:acme.internal.*`
+/// or `field:re:^_[a-z]+$`. Without the `re:` prefix, the pattern is a glob where `*`
+/// matches any sequence of characters and `?` matches a single character.
+#[derive(Debug, Clone)]
+struct Pattern {
+    kind: Option<SymbolKind>,
+    regex: Regex,
+}
+
+impl Pattern {
+    fn parse(pattern: &str) -> Result<Self, Error> {
+        let (kind, pattern) = match pattern.split_once(':') {
+            Some((prefix, rest)) => match SymbolKind::from_prefix(prefix) {
+                Some(kind) => (Some(kind), rest),
+                None => (None, pattern),
+            },
+            None => (None, pattern),
+        };
+        let regex = if let Some(regex) = pattern.strip_prefix("re:") {
+            Regex::new(regex)
+        } else {
+            Regex::new(&Self::glob_to_regex(pattern))
+        }
+        .map_err(|err| Error::Options(format!("invalid symbol pattern `{}`: {}", pattern, err)))?;
+        Ok(Self { kind, regex })
+    }
+
+    fn glob_to_regex(glob: &str) -> String {
+        let mut regex = String::from("^");
+        for c in glob.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                _ => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+        regex
+    }
+
+    fn applies_to(&self, kind: SymbolKind) -> bool {
+        self.kind.is_none() || self.kind == Some(kind)
+    }
+
+    fn matches(&self, names: &[&str]) -> bool {
+        names.iter().any(|name| self.regex.is_match(name))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A marker such as `@nodoc` which can be placed into descriptions to tag symbols.
+#[derive(Debug, Clone)]
+pub(crate) struct DescriptionTag {
+    regex: Regex,
+}
+
+impl DescriptionTag {
+    /// Create a new tag matcher from an option value. Empty tags disable the tag.
+    pub fn from_option(tag: &str) -> Result<Option<Self>, Error> {
+        if tag.is_empty() {
+            return Ok(None);
+        }
+        let regex = Regex::new(&format!(r"(^|\s){}(\s|$)", regex::escape(tag)))
+            .map_err(|err| Error::Options(format!("invalid tag `{}`: {}", tag, err)))?;
+        Ok(Some(Self { regex }))
+    }
+
+    /// Returns true when the given description contains the tag.
+    pub fn is_in(&self, desc: &str) -> bool {
+        self.regex.is_match(desc)
+    }
+
+    /// Removes all occurrences of the tag from the given description.
+    pub fn strip(&self, desc: &str) -> String {
+        self.regex.replace_all(desc, "$1").trim_end().to_string()
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Include and exclude patterns for classes, functions, fields, enums and aliases.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SymbolFilter {
+    includes: Vec<Pattern>,
+    excludes: Vec<Pattern>,
+    excluded_classes: Vec<String>,
+    include_private: bool,
+    hidden_tag: Option<DescriptionTag>,
+}
+
+impl SymbolFilter {
+    pub fn from_options(options: &Options) -> Result<Self, Error> {
+        Ok(Self {
+            includes: options
+                .included_symbols
+                .iter()
+                .map(|p| Pattern::parse(p))
+                .collect::<Result<_, _>>()?,
+            excludes: options
+                .excluded_symbols
+                .iter()
+                .map(|p| Pattern::parse(p))
+                .collect::<Result<_, _>>()?,
+            excluded_classes: options.excluded_classes.clone(),
+            include_private: options.include_private,
+            hidden_tag: DescriptionTag::from_option(&options.hidden_tag)?,
+        })
+    }
+
+    /// Returns true when the given description contains the hidden tag.
+    pub fn is_hidden(&self, desc: &str) -> bool {
+        self.hidden_tag.as_ref().is_some_and(|tag| tag.is_in(desc))
+    }
+
+    /// Removes all hidden tags from the given description.
+    pub fn strip_hidden_tag(&self, desc: &str) -> String {
+        match &self.hidden_tag {
+            Some(tag) => tag.strip(desc),
+            None => desc.to_string(),
+        }
+    }
+
+    /// Returns true when a member with the given visibility should be documented.
+    pub fn is_visible(&self, visibility: Visibility) -> bool {
+        self.include_private || visibility.is_public()
+    }
+
+    /// Returns true when a symbol with the given fully qualified name should be documented.
+    pub fn is_included(&self, kind: SymbolKind, name: &str) -> bool {
+        self.is_member_included(kind, name, name)
+    }
+
+    /// Returns true when a symbol should be documented. Patterns are matched against both,
+    /// the short name and the fully qualified name of the symbol (e.g. `_helper` and
+    /// `acme.SomeClass._helper`).
+    pub fn is_member_included(&self, kind: SymbolKind, name: &str, full_name: &str) -> bool {
+        if kind == SymbolKind::Class && self.excluded_classes.iter().any(|c| c == full_name) {
+            return false;
+        }
+        let names = [name, full_name];
+        let mut includes = self
+            .includes
+            .iter()
+            .filter(|p| p.applies_to(kind))
+            .peekable();
+        if includes.peek().is_some() && !includes.any(|p| p.matches(&names)) {
+            return false;
+        }
+        !self
+            .excludes
+            .iter()
+            .filter(|p| p.applies_to(kind))
+            .any(|p| p.matches(&names))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn filter(includes: &[&str], excludes: &[&str]) -> SymbolFilter {
+        SymbolFilter::from_options(&Options {
+            included_symbols: includes.iter().map(|p| p.to_string()).collect(),
+            excluded_symbols: excludes.iter().map(|p| p.to_string()).collect(),
+            excluded_classes: vec!["acme.Excluded".to_string()],
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn globs() {
+        let filter = filter(&[], &["_*", "acme.?ub.*"]);
+        assert!(!filter.is_included(SymbolKind::Function, "_helper"));
+        assert!(filter.is_included(SymbolKind::Function, "helper"));
+        assert!(!filter.is_included(SymbolKind::Class, "acme.sub.Thing"));
+        assert!(filter.is_included(SymbolKind::Class, "acme.subs.Thing"));
+        // globs are anchored and match dots literally
+        assert!(filter.is_included(SymbolKind::Class, "acmeXsub.Thing"));
+        assert!(filter.is_included(SymbolKind::Field, "my_field"));
+    }
+
+    #[test]
+    fn short_and_full_names() {
+        let filter = filter(&[], &["_*", "acme.SomeClass.hidden"]);
+        let is_included =
+            |name, full_name| filter.is_member_included(SymbolKind::Field, name, full_name);
+        assert!(!is_included("_value", "acme.SomeClass._value"));
+        assert!(!is_included("hidden", "acme.SomeClass.hidden"));
+        assert!(is_included("hidden", "acme.OtherClass.hidden"));
+    }
+
+    #[test]
+    fn kinds_and_regexes() {
+        let filter = filter(&[], &["class:acme.internal.*", "field:re:^_[a-z]+$"]);
+        assert!(!filter.is_included(SymbolKind::Class, "acme.internal.Thing"));
+        assert!(filter.is_included(SymbolKind::Function, "acme.internal.Thing"));
+        assert!(!filter.is_included(SymbolKind::Field, "_value"));
+        assert!(filter.is_included(SymbolKind::Field, "_value2"));
+        assert!(filter.is_included(SymbolKind::Function, "_value"));
+    }
+
+    #[test]
+    fn invalid_prefixes() {
+        // unknown kind prefixes are part of the pattern
+        let filter = filter(&[], &["acme:*"]);
+        assert!(!filter.is_included(SymbolKind::Class, "acme:Thing"));
+        assert!(filter.is_included(SymbolKind::Class, "acme.Thing"));
+        // invalid regular expressions are option errors
+        let options = Options {
+            excluded_symbols: vec!["re:(".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            SymbolFilter::from_options(&options),
+            Err(Error::Options(_))
+        ));
+    }
+
+    #[test]
+    fn description_tags() {
+        let tag = DescriptionTag::from_option("@nodoc").unwrap().unwrap();
+        assert!(tag.is_in("@nodoc"));
+        assert!(tag.is_in("Some text @nodoc"));
+        assert!(tag.is_in("Some text\n@nodoc\nmore text"));
+        // tags only match as whole words
+        assert!(!tag.is_in("Some text @nodocs"));
+        assert!(!tag.is_in("Some text x@nodoc"));
+        assert_eq!(tag.strip("Some @nodoc text"), "Some text");
+        assert_eq!(tag.strip("Some text\n@nodoc"), "Some text");
+        assert_eq!(tag.strip("Some text @nodocs"), "Some text @nodocs");
+        // tags are matched literally
+        let tag = DescriptionTag::from_option("(hidden)").unwrap().unwrap();
+        assert!(tag.is_in("Some (hidden) text"));
+        assert!(!tag.is_in("Some hidden text"));
+        // empty tags disable the tag
+        assert!(DescriptionTag::from_option("").unwrap().is_none());
+        let options = Options {
+            hidden_tag: String::new(),
+            ..Default::default()
+        };
+        let filter = SymbolFilter::from_options(&options).unwrap();
+        assert!(!filter.is_hidden("Some text @nodoc"));
+        assert_eq!(
+            filter.strip_hidden_tag("Some text @nodoc"),
+            "Some text @nodoc"
+        );
+    }
+
+    #[test]
+    fn visibility() {
+        let filter = filter(&[], &[]);
+        assert!(filter.is_visible(Visibility::Public));
+        assert!(!filter.is_visible(Visibility::Protected));
+        assert!(!filter.is_visible(Visibility::Package));
+        assert!(!filter.is_visible(Visibility::Private));
+        let options = Options {
+            include_private: true,
+            ..Default::default()
+        };
+        let filter = SymbolFilter::from_options(&options).unwrap();
+        assert!(filter.is_visible(Visibility::Private));
+    }
+
+    #[test]
+    fn includes_and_excludes() {
+        let filter = filter(&["class:acme.*"], &["class:acme.internal.*"]);
+        assert!(filter.is_included(SymbolKind::Class, "acme.Thing"));
+        assert!(!filter.is_included(SymbolKind::Class, "other.Thing"));
+        // excludes take precedence over includes
+        assert!(!filter.is_included(SymbolKind::Class, "acme.internal.Thing"));
+        // includes only restrict symbols of their own kind
+        assert!(filter.is_included(SymbolKind::Function, "other.run"));
+        // excluded classes are matched by full name
+        assert!(!filter.is_included(SymbolKind::Class, "acme.Excluded"));
+        assert!(filter.is_included(SymbolKind::Function, "acme.Excluded"));
+    }
+}
@@ -1,11 +1,26 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::Instant,
+};
 
 use itertools::Itertools;
+use regex::Regex;
 
 use crate::{
     error::Error,
-    generator::options::{Options, OutputOrder},
-    parser::{json::JsonDoc, types::*},
+    generator::{
+        constants::ConstantRules,
+        dependencies::TypeDependencies,
+        filter::{SymbolFilter, SymbolKind},
+        options::{Options, OutputOrder},
+        timings::Timings,
+    },
+    parser::{
+        json::{Definition, JsonDoc},
+        types::*,
+    },
 };
 
 // -------------------------------------------------------------------------------------------------
@@ -15,21 +30,90 @@ pub struct Library {
     pub classes: HashMap<String, Class>,
     pub enums: HashMap<String, Enum>,
     pub aliases: HashMap<String, Alias>,
+    pub builtins: Vec<Class>,
+    pub symbols: SymbolTable,
+    pub dependencies: TypeDependencies,
+    pub timings: Timings,
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 impl Library {
     /// generate a library from a given root directory or lua file with the given options
     pub fn from_path(path: &Path, options: &Options) -> Result<Self, Error> {
+        let filter = SymbolFilter::from_options(options)?;
+        let constant_rules = ConstantRules::from_options(options)?;
         println!("Parsing definitions: '{}'", path.to_string_lossy());
+        // LuaLS exports the std meta definitions of its configured runtime version
+        if !options.std_docs.is_empty() {
+            // LuaLS uses Lua 5.4 when no runtime version is configured
+            let runtime_version =
+                JsonDoc::runtime_version(path).unwrap_or_else(|| "Lua 5.4".to_string());
+            let lua_version = options.lua_version.unwrap_or_default();
+            if !runtime_version.eq_ignore_ascii_case(lua_version.luals_name()) {
+                println!(
+                    "\x1b[33mwarning: the LuaLS runtime version `{}` does not match the Lua version `{}`: std docs and Lua manual links will refer to different Lua versions\x1b[0m",
+                    runtime_version,
+                    lua_version.luals_name()
+                );
+            }
+        }
+        let start = Instant::now();
+        let definitions = JsonDoc::get(path, &options.std_docs)?;
+        let luals_export = start.elapsed();
+        let mut library =
+            Self::from_definitions(path, &definitions, &filter, &constant_rules, options)?;
+        library.timings.luals_export = luals_export;
+        Ok(library)
+    }
+
+    /// generate a library from the given LuaLS definitions of the library at the given path
+    pub fn from_definitions(
+        path: &Path,
+        definitions: &[Definition],
+        filter: &SymbolFilter,
+        constant_rules: &ConstantRules,
+        options: &Options,
+    ) -> Result<Self, Error> {
+        let std_modules = options.std_modules();
         let mut defs: Vec<Def> = vec![];
-        let definitions = JsonDoc::get(path)?;
+        let start = Instant::now();
+        let mut diagnostics = vec![];
         defs.append(
             &mut definitions
                 .iter()
-                .filter_map(|d| Def::from_definition(d, &options.namespace))
+                .filter_map(|d| {
+                    Def::from_definition(d, &options.namespaces, &std_modules, &mut diagnostics)
+                })
                 .collect::<Vec<Def>>(),
         );
-        Ok(Self::from_defs(defs, options))
+        let parsing = start.elapsed();
+
+        // print all types which failed to parse, with file paths relative to the library
+        for diagnostic in diagnostics.iter_mut() {
+            diagnostic.file = diagnostic
+                .file
+                .take()
+                .map(|file| JsonDoc::relative_path(path, &file.to_string_lossy()).unwrap_or(file));
+        }
+        if !diagnostics.is_empty() {
+            println!("type parse errors:");
+            for diagnostic in &diagnostics {
+                println!("  \x1b[33m{}\x1b[0m", diagnostic);
+            }
+        }
+
+        let start = Instant::now();
+        let mut library = Self::from_defs(defs, filter, constant_rules, options);
+        if let Some(builtins_path) = &options.builtins_path {
+            library.builtins = Self::custom_builtin_classes(library.builtins, builtins_path)?;
+        }
+        library.diagnostics = diagnostics;
+        library.timings = Timings {
+            parsing,
+            resolution: start.elapsed(),
+            ..Default::default()
+        };
+        Ok(library)
     }
 
     // a list of classes that correspond to lua types
@@ -58,11 +142,13 @@ impl Library {
             ),
             Self::builtin_class_desc("function", "A built-in type representing functions, [see details](https://www.lua.org/pil/2.6.html)"),
             Self::builtin_class_desc("table", "A built-in type representing associative arrays, [see details](https://www.lua.org/pil/2.5.html)"),
-            Self::builtin_class_desc("userdata", "A built-in type representing array values, [see details](https://www.lua.org/pil/28.1.html)."),
+            Self::builtin_class_desc("userdata", "A built-in type representing arbitrary C data, [see details](https://www.lua.org/pil/28.1.html)."),
             Self::builtin_class_desc(
                 "lightuserdata",
                 "A built-in type representing a pointer, [see details](https://www.lua.org/pil/28.5.html)",
             ),
+            Self::builtin_class_desc("thread", "A built-in type representing independent threads of execution, used to implement coroutines, [see details](https://www.lua.org/pil/9.1.html)"),
+            Self::builtin_class_desc("binary", "A helper type that represents a string of raw bytes, a subset of [string](string.md)"),
 
             Self::builtin_class_desc("integer", "A helper type that represents whole numbers, a subset of [number](number.md)"),
             Self::builtin_class_desc(
@@ -76,64 +162,116 @@ impl Library {
         ]
     }
 
-    fn resolve_string(&self, s: &str) -> Option<Kind> {
-        #[allow(clippy::manual_map)]
-        if let Some(class) = self.classes.get(s) {
-            Some(Kind::Class(class.clone()))
-        } else if let Some(alias) = self.aliases.get(s) {
-            Some(Kind::Alias(Box::new(alias.clone())))
-        } else if let Some(enumref) = self.enums.get(s) {
-            Some(Kind::EnumRef(Box::new(enumref.clone())))
-        } else {
-            None
+    // override or extend builtin classes with markdown files from the given directory:
+    // `<name>.md` files replace the description of the builtin type `name` or add a new one
+    fn custom_builtin_classes(mut builtins: Vec<Class>, path: &Path) -> Result<Vec<Class>, Error> {
+        if !path.is_dir() {
+            return Err(Error::Options(format!(
+                "builtins path does not exists: `{}`",
+                path.to_string_lossy(),
+            )));
+        }
+        let mut files = std::fs::read_dir(path)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|file| file.extension().is_some_and(|ext| ext == "md"))
+            .collect::<Vec<_>>();
+        files.sort();
+        for file in files {
+            let name = file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let desc = std::fs::read_to_string(&file)?;
+            if let Some(class) = builtins.iter_mut().find(|c| c.name == name) {
+                class.desc = desc;
+            } else {
+                builtins.push(Self::builtin_class_desc(&name, &desc));
+            }
         }
+        Ok(builtins)
     }
 
-    // cross-reference parsed Kinds as existing classes, enums and aliases
-    fn resolve_kind(&self, kind: &Kind) -> Kind {
-        match kind.clone() {
-            Kind::Unresolved(s) => self.resolve_string(&s).unwrap_or(kind.clone()),
-            Kind::Array(bk) => Kind::Array(Box::new(self.resolve_kind(bk.as_ref()))),
-            Kind::Nullable(bk) => Kind::Nullable(Box::new(self.resolve_kind(bk.as_ref()))),
-            Kind::Table(key, value) => Kind::Table(
-                Box::new(self.resolve_kind(key.as_ref())),
-                Box::new(self.resolve_kind(value.as_ref())),
-            ),
-            Kind::Enum(kinds) => Kind::Enum(kinds.iter().map(|k| self.resolve_kind(k)).collect()),
-            Kind::Function(f) => {
-                let mut fun = f.clone();
-                self.resolve_function(&mut fun);
-                Kind::Function(fun)
+    // names of all builtin types which are referenced by the library or by other builtins.
+    // builtins which are not part of the default builtin classes are always included.
+    pub fn referenced_builtins(&self) -> HashSet<String> {
+        let mut names = HashSet::new();
+        for class in self.classes.values() {
+            names.extend(class.collect_builtin_types());
+        }
+        for alias in self.aliases.values() {
+            names.extend(alias.kind.collect_builtin_types());
+        }
+        let default_names = Self::builtin_classes()
+            .into_iter()
+            .map(|c| c.name)
+            .collect::<HashSet<_>>();
+        for class in &self.builtins {
+            if !default_names.contains(&class.name) {
+                names.insert(class.name.clone());
             }
-            Kind::Variadic(v) => Kind::Variadic(Box::new(self.resolve_kind(v.as_ref()))),
-            Kind::Object(hm) => {
-                let mut obj = hm.clone();
-                for (key, value) in hm.iter() {
-                    obj.insert(key.clone(), Box::new(self.resolve_kind(value.as_ref())));
+        }
+        // add builtins which are linked in descriptions of other referenced builtins
+        loop {
+            let mut new_names = names.clone();
+            for class in self.builtins.iter().filter(|c| names.contains(&c.name)) {
+                for other in &self.builtins {
+                    if class.desc.contains(&format!("({}.md)", other.name)) {
+                        new_names.insert(other.name.clone());
+                    }
                 }
-                Kind::Object(obj)
             }
-            _ => kind.clone(),
+            if new_names == names {
+                break;
+            }
+            names = new_names;
         }
+        names
     }
 
-    fn resolve_function(&self, f: &mut Function) {
-        for p in f.params.iter_mut() {
-            p.kind = self.resolve_kind(&p.kind)
+    // intern all classes, aliases and enums, so resolved kinds can refer to them by id.
+    // classes take precedence over aliases and enums with the same name.
+    fn build_symbols(&mut self) {
+        let mut symbols = SymbolTable::default();
+        for class in self.classes.values().sorted_by(|a, b| a.name.cmp(&b.name)) {
+            symbols.insert(Symbol {
+                symbol_type: SymbolType::Class,
+                name: class.name.clone(),
+                file: class.file.clone(),
+                scope: class.scope.clone(),
+            });
         }
-        for r in f.returns.iter_mut() {
-            r.kind = self.resolve_kind(&r.kind)
+        for alias in self.aliases.values().sorted_by(|a, b| a.name.cmp(&b.name)) {
+            symbols.insert(Symbol {
+                symbol_type: SymbolType::Alias,
+                name: alias.name.clone(),
+                file: alias.file.clone(),
+                scope: Scope::Global,
+            });
         }
+        for enumref in self.enums.values().sorted_by(|a, b| a.name.cmp(&b.name)) {
+            symbols.insert(Symbol {
+                symbol_type: SymbolType::Enum,
+                name: enumref.name.clone(),
+                file: enumref.file.clone(),
+                scope: Scope::Global,
+            });
+        }
+        self.symbols = symbols;
     }
 
     fn resolve_classes(&mut self) {
-        let l = self.clone();
-        for (_, c) in self.classes.iter_mut() {
+        let symbols = &self.symbols;
+        for c in self.classes.values_mut() {
             for f in c.fields.iter_mut() {
-                f.kind = l.resolve_kind(&f.kind)
+                f.kind = symbols.resolve_kind(&f.kind)
+            }
+            for f in c.functions.iter_mut().chain(c.constructors.iter_mut()) {
+                symbols.resolve_function(f)
             }
-            for f in c.functions.iter_mut() {
-                l.resolve_function(f)
+            for o in c.operators.iter_mut() {
+                o.operand = o.operand.as_ref().map(|k| symbols.resolve_kind(k));
+                o.result = o.result.as_ref().map(|k| symbols.resolve_kind(k));
             }
         }
     }
@@ -149,17 +287,241 @@ impl Library {
             fields: vec![],
             functions: vec![],
             constants: vec![],
+            operators: vec![],
+            constructors: vec![],
+            constructed_by: vec![],
             enums: vec![],
         }
     }
 
-    // generate Library from a list of Defs, applying the given options.
-    fn from_defs(defs: Vec<Def>, options: &Options) -> Self {
+    // get or create the class which holds global functions and variables of the given class.
+    // when organizing by files, globals are moved into a separate "globals" class per file.
+    // members which are defined in other files than the class get rendered on their own file
+    // pages, see `Class::files`.
+    fn globals_class(
+        &mut self,
+        class_name: &str,
+        file: &Option<PathBuf>,
+        line_number: Option<u32>,
+        options: &Options,
+    ) -> &mut Class {
+        let mut target_class_name = class_name.to_string();
+        if options.order == OutputOrder::ByFile && target_class_name == "global" {
+            if let Some(file) = file {
+                let file_stem = file.file_stem().map(|f| f.to_string_lossy());
+                target_class_name = format!("{} globals", file_stem.unwrap()).to_string();
+            }
+        }
+        if !self.classes.contains_key(&target_class_name) {
+            self.classes.insert(
+                target_class_name.clone(),
+                Class {
+                    file: file.clone(),
+                    line_number,
+                    scope: Scope::from_name(
+                        class_name,
+                        &options.namespaces,
+                        &options.std_modules(),
+                    ),
+                    name: class_name.to_string(),
+                    functions: vec![],
+                    fields: vec![],
+                    enums: vec![],
+                    constants: vec![],
+                    operators: vec![],
+                    constructors: vec![],
+                    constructed_by: vec![],
+                    desc: String::new(),
+                },
+            );
+        }
+        self.classes
+            .get_mut(&target_class_name)
+            .expect("expecting globals class to exist")
+    }
+
+    // rewrite the `See:` sections, which LuaLS adds for `@see` annotations to descriptions,
+    // into "See also" lists. targets which refer to symbols get rendered as code spans, which
+    // get linked when rendering descriptions. returns all symbol references which can't be
+    // linked as (symbol, target) tuples.
+    fn resolve_see_references(&mut self) -> Vec<(String, String)> {
+        let mut references = vec![];
+        let mut rewrite = |symbol: String, desc: &mut String| {
+            let (new_desc, targets) = see_also(desc);
+            *desc = new_desc;
+            references.extend(targets.into_iter().map(|target| (symbol.clone(), target)));
+        };
+        for class in self.classes.values_mut() {
+            rewrite(class.name.clone(), &mut class.desc);
+            for v in class.fields.iter_mut().chain(class.constants.iter_mut()) {
+                let symbol = format!("{}.{}", class.name, v.name.clone().unwrap_or_default());
+                if let Some(desc) = v.desc.as_mut() {
+                    rewrite(symbol, desc);
+                }
+            }
+            for f in class
+                .functions
+                .iter_mut()
+                .chain(class.constructors.iter_mut())
+            {
+                let symbol = format!("{}:{}", class.name, f.name.clone().unwrap_or_default());
+                if let Some(desc) = f.desc.as_mut() {
+                    rewrite(symbol, desc);
+                }
+            }
+            for o in class.operators.iter_mut() {
+                let symbol = format!("{} operator `{}`", class.name, o.name);
+                if let Some(desc) = o.desc.as_mut() {
+                    rewrite(symbol, desc);
+                }
+            }
+        }
+        for alias in self.aliases.values_mut() {
+            if let Some(desc) = alias.desc.as_mut() {
+                rewrite(alias.name.clone(), desc);
+            }
+        }
+        let mut unresolved = references
+            .into_iter()
+            .filter(|(_, target)| self.description_target(target).is_none())
+            .collect::<Vec<_>>();
+        unresolved.sort();
+        unresolved
+    }
+
+    // remove all defs and class members which are excluded by the given filter or which
+    // got marked as hidden in their descriptions, and strip hidden tags from the rest.
+    fn apply_filter(defs: Vec<Def>, filter: &SymbolFilter) -> Vec<Def> {
+        let hidden_classes = defs
+            .iter()
+            .filter_map(|d| match d {
+                Def::Class(c) if filter.is_hidden(&c.desc) => Some(c.name.clone()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+        let base_is_included = |name: &str| {
+            Class::get_base(name).is_none_or(|base| {
+                !hidden_classes.contains(base) && filter.is_included(SymbolKind::Class, base)
+            })
+        };
+        let is_hidden = |desc: &Option<String>| desc.as_ref().is_some_and(|d| filter.is_hidden(d));
+        let strip_hidden = |desc: Option<String>| desc.map(|d| filter.strip_hidden_tag(&d));
+        defs.into_iter()
+            .filter_map(|d| match d {
+                Def::Alias(a) => (filter.is_included(SymbolKind::Alias, &a.name)
+                    && !is_hidden(&a.desc))
+                .then(|| {
+                    Def::Alias(Alias {
+                        desc: strip_hidden(a.desc.clone()),
+                        ..a
+                    })
+                }),
+                Def::Enum(e) => (filter.is_included(SymbolKind::Enum, &e.name)
+                    && !filter.is_hidden(&e.desc)
+                    && base_is_included(&e.name))
+                .then(|| {
+                    Def::Enum(Enum {
+                        desc: filter.strip_hidden_tag(&e.desc),
+                        ..e
+                    })
+                }),
+                Def::Function(f) => {
+                    let name = f.name.clone().unwrap_or_default();
+                    let short_name = Class::get_end(&name).unwrap_or(&name);
+                    (filter.is_member_included(SymbolKind::Function, short_name, &name)
+                        && !is_hidden(&f.desc)
+                        && base_is_included(&name))
+                    .then(|| {
+                        Def::Function(Function {
+                            desc: strip_hidden(f.desc.clone()),
+                            ..f
+                        })
+                    })
+                }
+                Def::Var(v) => {
+                    let name = v.name.clone().unwrap_or_default();
+                    let short_name = Class::get_end(&name).unwrap_or(&name);
+                    (filter.is_member_included(SymbolKind::Field, short_name, &name)
+                        && !is_hidden(&v.desc)
+                        && base_is_included(&name))
+                    .then(|| {
+                        Def::Var(Var {
+                            desc: strip_hidden(v.desc.clone()),
+                            ..v
+                        })
+                    })
+                }
+                Def::Class(mut c) => {
+                    if !filter.is_included(SymbolKind::Class, &c.name)
+                        || hidden_classes.contains(&c.name)
+                    {
+                        return None;
+                    }
+                    let class_name = c.name.clone();
+                    c.fields.retain(|v| {
+                        if !filter.is_visible(v.visibility) || is_hidden(&v.desc) {
+                            return false;
+                        }
+                        let name = v.name.clone().unwrap_or_default();
+                        let full_name = format!("{}.{}", class_name, name);
+                        filter.is_member_included(SymbolKind::Field, &name, &full_name)
+                    });
+                    c.functions.retain(|f| {
+                        if !filter.is_visible(f.visibility) || is_hidden(&f.desc) {
+                            return false;
+                        }
+                        let name = f.name.clone().unwrap_or_default();
+                        let full_name = format!("{}.{}", class_name, name);
+                        filter.is_member_included(SymbolKind::Function, &name, &full_name)
+                    });
+                    for v in c.fields.iter_mut() {
+                        v.desc = strip_hidden(v.desc.take());
+                    }
+                    // constructors are named like the class they construct
+                    c.constructors.retain(|f| {
+                        if !filter.is_visible(f.visibility) || is_hidden(&f.desc) {
+                            return false;
+                        }
+                        let name = f.name.clone().unwrap_or_default();
+                        let short_name = Class::get_end(&name).unwrap_or(&name);
+                        filter.is_member_included(SymbolKind::Function, short_name, &name)
+                    });
+                    // operators are matched by their annotation name, e.g. `add`
+                    c.operators.retain(|o| {
+                        let full_name = format!("{}.{}", class_name, o.name);
+                        !is_hidden(&o.desc)
+                            && filter.is_member_included(SymbolKind::Function, &o.name, &full_name)
+                    });
+                    for f in c.functions.iter_mut().chain(c.constructors.iter_mut()) {
+                        f.desc = strip_hidden(f.desc.take());
+                    }
+                    for o in c.operators.iter_mut() {
+                        o.desc = strip_hidden(o.desc.take());
+                    }
+                    c.desc = filter.strip_hidden_tag(&c.desc);
+                    Some(Def::Class(c))
+                }
+            })
+            .collect()
+    }
+
+    // generate Library from a list of Defs, applying the given filter, rules and options.
+    fn from_defs(
+        defs: Vec<Def>,
+        filter: &SymbolFilter,
+        constant_rules: &ConstantRules,
+        options: &Options,
+    ) -> Self {
+        // remove excluded symbols before resolving, so no links to them get created
+        let defs = Self::apply_filter(defs, filter);
+        let std_modules = options.std_modules();
+
         // sort defs into hasmaps of classes, enums and aliases
         let mut classes = HashMap::new();
         let mut enums = HashMap::new();
         let mut aliases = HashMap::new();
         let mut dangling_functions = vec![];
+        let mut dangling_vars = vec![];
         for d in defs.iter() {
             match d {
                 Def::Alias(a) => {
@@ -168,10 +530,14 @@ impl Library {
                 Def::Enum(e) => {
                     enums.insert(e.name.clone(), e.clone());
                 }
-                Def::Class(c) => {
-                    classes.insert(c.name.clone(), c.clone());
-                }
+                Def::Class(c) => match classes.get_mut(&c.name) {
+                    Some(class) => Class::merge(class, c.clone()),
+                    None => {
+                        classes.insert(c.name.clone(), c.clone());
+                    }
+                },
                 Def::Function(f) => dangling_functions.push(f.clone()),
+                Def::Var(v) => dangling_vars.push(v.clone()),
             }
         }
 
@@ -180,20 +546,13 @@ impl Library {
             classes,
             enums,
             aliases,
+            builtins: Self::builtin_classes(),
+            symbols: SymbolTable::default(),
+            dependencies: TypeDependencies::default(),
+            timings: Timings::default(),
+            diagnostics: vec![],
         };
 
-        // transform any unresolved Kind to the appropriate class or alias
-        // by cross referencing the hashmaps of the library
-        library.resolve_classes();
-        let mut aliases = library.aliases.clone();
-        aliases
-            .iter_mut()
-            .for_each(|(_, a)| a.kind = library.resolve_kind(&a.kind));
-        library.aliases = aliases;
-        dangling_functions
-            .iter_mut()
-            .for_each(|f| library.resolve_function(f));
-
         // assign enums to new or existing classes
         for (k, e) in library.enums.iter() {
             let base = Class::get_base(k).unwrap_or("global").to_string();
@@ -241,12 +600,15 @@ impl Library {
                         Class {
                             file: e.file.clone(),
                             line_number: e.line_number,
-                            scope: Scope::from_name(&base, &options.namespace),
+                            scope: Scope::from_name(&base, &options.namespaces, &std_modules),
                             name: base,
                             functions: vec![],
                             fields: vec![],
                             enums: vec![e.strip_base()],
                             constants: vec![],
+                            operators: vec![],
+                            constructors: vec![],
+                            constructed_by: vec![],
                             desc: String::new(),
                         },
                     );
@@ -255,74 +617,97 @@ impl Library {
         }
 
         // assign global functions to new or existing classes
-        for f in dangling_functions.iter_mut() {
+        for f in dangling_functions.iter() {
             let function_name = f.name.clone().unwrap_or_default();
-            let class_name = Class::get_base(&function_name)
-                .unwrap_or("global")
-                .to_string();
-            let mut target_class_name = class_name.clone();
-            let mut added_to_existing_class = false;
-            match options.order {
-                OutputOrder::ByFile => {
-                    if target_class_name == "global" {
-                        if let Some(file) = &f.file {
-                            let file_stem = file.file_stem().map(|f| f.to_string_lossy());
-                            target_class_name =
-                                format!("{} globals", file_stem.unwrap()).to_string();
-                        }
-                    }
-                    // move globals into a separate "globals" file
-                    if let Some((_, class)) = library
-                        .classes
-                        .iter_mut()
-                        .find(|(name, c)| *name == &target_class_name && c.file == f.file)
-                    {
-                        let f = f.strip_base();
-                        if !class.functions.iter().any(|f2| f2.name == f.name) {
-                            class.functions.push(f)
-                        }
-                        added_to_existing_class = true;
-                    }
-                }
-                OutputOrder::ByClass => {
-                    // move globals into the source file
-                    if let Some((_, class)) = library
-                        .classes
-                        .iter_mut()
-                        .find(|(name, _c)| *name == &class_name)
-                    {
-                        let f = f.strip_base();
-                        if !class.functions.iter().any(|f2| f2.name == f.name) {
-                            class.functions.push(f)
-                        }
-                        added_to_existing_class = true;
-                    }
-                }
+            // functions named like a class construct instances of the class
+            if let Some(class) = library.classes.get_mut(&function_name) {
+                class.constructors.push(f.clone());
+                continue;
             }
-            if !added_to_existing_class {
-                library.classes.insert(
-                    target_class_name,
-                    Class {
-                        file: f.file.clone(),
-                        line_number: f.line_number,
-                        scope: Scope::from_name(&class_name, &options.namespace),
-                        name: class_name,
-                        functions: vec![f.strip_base()],
-                        fields: vec![],
-                        enums: vec![],
-                        constants: vec![],
-                        desc: String::new(),
-                    },
-                );
+            let class = library.globals_class(
+                Class::get_base(&function_name).unwrap_or("global"),
+                &f.file,
+                f.line_number,
+                options,
+            );
+            let f = f.strip_base();
+            if !class.functions.iter().any(|f2| f2.name == f.name) {
+                class.functions.push(f)
             }
         }
 
-        // apply class excludes
+        // assign global variables and fields of namespace tables to new or existing classes
+        for v in dangling_vars.iter() {
+            let var_name = v.name.clone().unwrap_or_default();
+            let class = library.globals_class(
+                Class::get_base(&var_name).unwrap_or("global"),
+                &v.file,
+                v.line_number,
+                options,
+            );
+            let v = v.strip_base();
+            if !class
+                .fields
+                .iter()
+                .chain(&class.constants)
+                .any(|v2| v2.name == v.name)
+            {
+                class.fields.push(v)
+            }
+        }
+
+        // apply class excludes to classes which got created for globals above
         library
             .classes
-            .retain(|name, _| !options.excluded_classes.contains(name));
+            .retain(|_, class| filter.is_included(SymbolKind::Class, &class.name));
+
+        // transform any unresolved Kind to the appropriate class or alias by cross
+        // referencing the symbols of the library, once all classes got created
+        library.build_symbols();
+        library.resolve_classes();
+        for alias in library.aliases.values_mut() {
+            alias.kind = library.symbols.resolve_kind(&alias.kind);
+        }
+
+        // move class functions which are named like other classes and `__call` metamethods
+        // into the constructors of the classes they construct
+        let class_names = library
+            .classes
+            .values()
+            .map(|c| c.name.clone())
+            .collect::<HashSet<_>>();
+        let mut constructors = vec![];
+        for class in library.classes.values_mut() {
+            let full_name =
+                |f: &Function| format!("{}.{}", class.name, f.name.clone().unwrap_or_default());
+            let (class_constructors, functions): (Vec<_>, Vec<_>) =
+                class.functions.iter().cloned().partition(|f| {
+                    f.name.as_deref() == Some("__call") || class_names.contains(&full_name(f))
+                });
+            for mut f in class_constructors {
+                if f.name.as_deref() == Some("__call") {
+                    // the first argument of `__call` is the class table itself
+                    if !f.params.is_empty() {
+                        f.params.remove(0);
+                    }
+                    f.name = Some(class.name.clone());
+                    constructors.push((class.name.clone(), f));
+                } else {
+                    let name = full_name(&f);
+                    f.name = Some(name.clone());
+                    constructors.push((name, f));
+                }
+            }
+            class.functions = functions;
+        }
+        for (class_name, f) in constructors {
+            if let Some(class) = library.classes.get_mut(&class_name) {
+                class.constructors.push(f);
+            }
+        }
 
         // extract constants, make functions, fields and constants unique and sort them
+        let mut reclassified = vec![];
         for class in library.classes.values_mut() {
             let mut functions = class
                 .functions
@@ -332,6 +717,14 @@ impl Library {
                 .collect::<Vec<_>>();
             functions.sort_by_key(|f| (f.file.clone(), f.line_number));
 
+            let mut constructors = class
+                .constructors
+                .clone()
+                .into_iter()
+                .unique_by(|f| f.to_string())
+                .collect::<Vec<_>>();
+            constructors.sort_by_key(|f| (f.file.clone(), f.line_number));
+
             let mut enums = class
                 .enums
                 .clone()
@@ -340,32 +733,96 @@ impl Library {
                 .collect::<Vec<_>>();
             enums.sort_by_key(|e| (e.file.clone(), e.line_number));
 
-            let mut fields = class
+            let mut fields = vec![];
+            let mut constants = vec![];
+            for mut field in class
                 .fields
                 .clone()
                 .into_iter()
-                .filter(Var::is_not_constant)
-                // HACK: remove table properties from classes, assuming they are nested classes
-                .filter(|v| !matches!(v.kind, Kind::Lua(LuaKind::Table)))
                 .unique_by(|f| f.name.clone())
-                .collect::<Vec<_>>();
+            {
+                let field_name = field.name.clone().unwrap_or_default();
+                let full_name = if class.name == "global" {
+                    field_name
+                } else {
+                    format!("{}.{}", class.name, field_name)
+                };
+                if let Some(reason) = constant_rules.classify(&field) {
+                    field.desc = field.desc.map(|d| constant_rules.strip_tag(&d));
+                    reclassified.push(format!("{} -> constant ({})", full_name, reason));
+                    constants.push(field);
+                } else if matches!(field.kind, Kind::Lua(LuaKind::Table))
+                    && class_names.iter().any(|name| {
+                        name == &full_name || name.starts_with(&(full_name.clone() + "."))
+                    })
+                {
+                    // table fields which are nested classes get documented as classes
+                    reclassified.push(format!("{} -> nested class", full_name));
+                } else {
+                    fields.push(field);
+                }
+            }
             fields.sort_by_key(|f| (f.file.clone(), f.line_number));
-
-            let mut constants = class
-                .fields
-                .clone()
-                .into_iter()
-                .filter(Var::is_constant)
-                .unique_by(|f| f.name.clone())
-                .collect::<Vec<_>>();
             constants.sort_by_key(|c| (c.file.clone(), c.line_number));
 
             class.functions = functions;
+            class.constructors = constructors;
             class.fields = fields;
             class.enums = enums;
             class.constants = constants;
         }
 
+        // collect the classes whose constructors create instances of other classes
+        let mut constructed_by = HashMap::<String, Vec<String>>::new();
+        for class in library.classes.values() {
+            for name in class.constructed_classes(&library.symbols) {
+                if name != class.name {
+                    constructed_by
+                        .entry(name)
+                        .or_default()
+                        .push(class.name.clone());
+                }
+            }
+        }
+        for (name, mut class_names) in constructed_by {
+            if let Some(class) = library.classes.get_mut(&name) {
+                class_names.sort();
+                class_names.dedup();
+                class.constructed_by = class_names;
+            }
+        }
+
+        // print all fields which are not documented as properties
+        if options.verbose && !reclassified.is_empty() {
+            reclassified.sort();
+            println!("reclassified fields:");
+            for field in reclassified {
+                println!("  {}", field);
+            }
+        }
+
+        // build the struct and alias dependency graph, which is used when rendering pages
+        library.dependencies =
+            TypeDependencies::new(&library.classes, &library.aliases, &library.symbols);
+
+        // print all structs and aliases which refer to each other
+        let cycles = library.dependencies.cycles();
+        if !cycles.is_empty() {
+            println!("reference cycles:");
+            for cycle in cycles {
+                println!("  {}", cycle.join(" <-> "));
+            }
+        }
+
+        // rewrite `@see` references and print the ones which can't be linked
+        let unresolved = library.resolve_see_references();
+        if !unresolved.is_empty() {
+            println!("unresolved @see references:");
+            for (symbol, target) in unresolved {
+                println!("  {} -> {}", symbol, target);
+            }
+        }
+
         // debug print everything that includes some unresolved Kind or is empty
         if !library.classes.is_empty() {
             println!("classes:");
@@ -403,3 +860,444 @@ impl Library {
         library
     }
 }
+
+// -------------------------------------------------------------------------------------------------
+
+// `@see` targets, which LuaLS renders as markdown links into the Lua sources
+static SEE_LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[([^\]]+)\]\(([^\)]*)\)\s*(.*)$").unwrap());
+
+// rewrite a `See: target` line or `See:` list into a "See also" list. returns the new description
+// and all targets which refer to symbols.
+fn see_also(desc: &str) -> (String, Vec<String>) {
+    let lines = desc.lines().collect::<Vec<_>>();
+    let Some(start) = lines
+        .iter()
+        .position(|l| l.trim_start().starts_with("See:"))
+    else {
+        return (desc.to_string(), vec![]);
+    };
+    let mut items = vec![];
+    let first = lines[start].trim_start()["See:".len()..].trim();
+    if !first.is_empty() {
+        items.push(first);
+    }
+    let mut end = start + 1;
+    while let Some(item) = lines
+        .get(end)
+        .and_then(|l| l.trim_start().strip_prefix(['*', '-']))
+    {
+        items.push(item.trim());
+        end += 1;
+    }
+    let mut targets = vec![];
+    let mut list = vec!["See also:".to_string()];
+    for item in items {
+        let (entry, comment) = if let Some(captures) = SEE_LINK_RE.captures(item) {
+            let (text, url) = (&captures[1], &captures[2]);
+            let entry = if url.is_empty() || url.starts_with("file://") {
+                targets.push(text.to_string());
+                format!("`{}`", text)
+            } else {
+                format!("[{}]({})", text, url)
+            };
+            (entry, captures[3].to_string())
+        } else {
+            let (target, comment) = item.split_once(' ').unwrap_or((item, ""));
+            let entry = if target.starts_with("http://") || target.starts_with("https://") {
+                format!("<{}>", target)
+            } else {
+                targets.push(target.to_string());
+                format!("`{}`", target)
+            };
+            (entry, comment.trim().to_string())
+        };
+        if comment.is_empty() {
+            list.push(format!("- {}", entry));
+        } else {
+            list.push(format!("- {} {}", entry, comment));
+        }
+    }
+    let desc = lines[..start]
+        .iter()
+        .map(|l| l.to_string())
+        .chain(list)
+        .chain(lines[end..].iter().map(|l| l.to_string()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    (desc, targets)
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use std::time::Instant;
+
+    use super::*;
+
+    fn class(name: &str, options: &Options) -> Class {
+        let scope = Scope::from_name(name, &options.namespaces, &options.std_modules());
+        Class {
+            file: Some("file:///library/acme.lua".into()),
+            ..Class::new(name, scope)
+        }
+    }
+
+    fn library(defs: Vec<Def>, options: &Options) -> Library {
+        let filter = SymbolFilter::from_options(options).unwrap();
+        let constant_rules = ConstantRules::from_options(options).unwrap();
+        Library::from_defs(defs, &filter, &constant_rules, options)
+    }
+
+    fn by_class_options() -> Options {
+        Options {
+            order: OutputOrder::ByClass,
+            namespaces: vec!["acme".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn constructors_and_operators() {
+        let options = Options {
+            excluded_symbols: vec!["function:acme.Factory.sub".to_string()],
+            ..by_class_options()
+        };
+        let product = Kind::Unresolved("acme.Product".to_string());
+        let operator = |name: &str| Operator {
+            file: None,
+            line_number: None,
+            name: name.to_string(),
+            operand: None,
+            result: None,
+            desc: None,
+        };
+        let factory = Class {
+            constructors: vec![
+                Function {
+                    returns: vec![Var::unnamed(product.clone())],
+                    ..Function::new("acme.Factory")
+                },
+                Function {
+                    desc: Some("Hidden @nodoc".to_string()),
+                    ..Function::new("acme.Factory")
+                },
+            ],
+            functions: vec![Function {
+                returns: vec![Var::unnamed(product)],
+                ..Function::new("Product")
+            }],
+            operators: vec![operator("add"), operator("sub")],
+            ..class("acme.Factory", &options)
+        };
+        let library = library(
+            vec![
+                Def::Class(factory),
+                Def::Class(class("acme.Product", &options)),
+                Def::Class(class("acme.Factory.Product", &options)),
+            ],
+            &options,
+        );
+        let factory = &library.classes["acme.Factory"];
+        assert_eq!(factory.constructors.len(), 1);
+        assert_eq!(
+            factory
+                .operators
+                .iter()
+                .map(|o| &o.name)
+                .collect::<Vec<_>>(),
+            vec!["add"]
+        );
+        // functions named like other classes construct them
+        assert!(factory.functions.is_empty());
+        assert_eq!(
+            library.classes["acme.Factory.Product"].constructors.len(),
+            1
+        );
+        assert_eq!(
+            library.classes["acme.Product"].constructed_by,
+            vec!["acme.Factory", "acme.Factory.Product"]
+        );
+        assert!(library.classes["acme.Factory"].constructed_by.is_empty());
+    }
+
+    #[test]
+    fn see_references() {
+        let options = by_class_options();
+        let some_class = Class {
+            functions: vec![Function::new("run")],
+            ..class("acme.SomeClass", &options)
+        };
+        let mut library = library(
+            vec![
+                Def::Class(some_class),
+                Def::Class(class("acme.OtherClass", &options)),
+            ],
+            &options,
+        );
+        let class = library.classes.get_mut("acme.SomeClass").unwrap();
+        class.desc = [
+            "Class docs",
+            "",
+            "See:",
+            "  * [acme.OtherClass](file:///library/acme.lua#10)",
+            "  * [acme.SomeClass:run](file:///library/acme.lua#12) runs it",
+            "  * [OtherClass](file:///library/acme.lua#10)",
+            "  * https://www.lua.org The Lua manual",
+        ]
+        .join("\n");
+        class.functions[0].desc = Some("Runs\nSee: [run](file:///library/acme.lua#12)".into());
+        let unresolved = library.resolve_see_references();
+        let class = &library.classes["acme.SomeClass"];
+        assert_eq!(
+            class.desc,
+            [
+                "Class docs",
+                "",
+                "See also:",
+                "- `acme.OtherClass`",
+                "- `acme.SomeClass:run` runs it",
+                "- `OtherClass`",
+                "- <https://www.lua.org> The Lua manual",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            class.functions[0].desc.as_deref(),
+            Some("Runs\nSee also:\n- `run`")
+        );
+        // short names are not qualified with the namespace or class of the symbol
+        assert_eq!(
+            unresolved,
+            vec![
+                ("acme.SomeClass".to_string(), "OtherClass".to_string()),
+                ("acme.SomeClass:run".to_string(), "run".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn member_anchors() {
+        let options = by_class_options();
+        let some_class = Class {
+            desc: "Use `acme.SomeClass.run` or `acme.SomeClass:run()`".to_string(),
+            fields: vec![Var::new("run", Kind::Lua(LuaKind::Boolean))],
+            functions: vec![Function::new("run")],
+            ..class("acme.SomeClass", &options)
+        };
+        let library = library(vec![Def::Class(some_class)], &options);
+        let docs = library.export_docs(&options);
+        let (_, page) = docs
+            .iter()
+            .find(|(name, _)| name == "acme/SomeClass")
+            .unwrap();
+        // same-named fields and functions get distinct anchors
+        assert!(page.contains("<a name=\"acmesomeclass--run\"></a>"));
+        assert!(page.contains("<a name=\"acmesomeclass--run-fn\"></a>"));
+        assert!(
+            page.contains("[`acme.SomeClass.run`](../../API/acme/SomeClass.md#acmesomeclass--run)")
+        );
+        assert!(page.contains(
+            "[`acme.SomeClass:run()`](../../API/acme/SomeClass.md#acmesomeclass--run-fn)"
+        ));
+        assert!(crate::generator::links::check_links(&docs, &[]).is_empty());
+    }
+
+    #[test]
+    fn description_links() {
+        let options = by_class_options();
+        let some_class = Class {
+            desc: "Uses `SomeAlias`, `acme.SomeClass.Mode` and ``SomeAlias``".to_string(),
+            fields: vec![Var::new("alias", Kind::Unresolved("SomeAlias".into()))],
+            ..class("acme.SomeClass", &options)
+        };
+        let other_class = Class {
+            desc: "Uses `acme.SomeClass.Mode`, but not `SomeAlias`".to_string(),
+            ..class("acme.OtherClass", &options)
+        };
+        let library = library(
+            vec![
+                Def::Class(some_class),
+                Def::Class(other_class),
+                Def::Enum(Enum {
+                    file: None,
+                    line_number: None,
+                    name: "acme.SomeClass.Mode".to_string(),
+                    desc: String::new(),
+                }),
+                Def::Alias(Alias::new("SomeAlias", Kind::Lua(LuaKind::String))),
+            ],
+            &options,
+        );
+        let docs = library.export_docs(&options);
+        let page = |path: &str| &docs.iter().find(|(name, _)| name == path).unwrap().1;
+        let some_class = page("acme/SomeClass");
+        assert!(some_class.contains("[`SomeAlias`](#somealias)"));
+        assert!(some_class
+            .contains("[`acme.SomeClass.Mode`](../../API/acme/SomeClass.md#acmesomeclass--mode)"));
+        assert!(some_class.contains("``SomeAlias``"));
+        // aliases are only linked on the pages they are inlined into
+        let other_class = page("acme/OtherClass");
+        assert!(other_class
+            .contains("[`acme.SomeClass.Mode`](../../API/acme/SomeClass.md#acmesomeclass--mode)"));
+        assert!(other_class.contains("but not `SomeAlias`"));
+        assert!(crate::generator::links::check_links(&docs, &[]).is_empty());
+    }
+
+    #[test]
+    fn unique_anchors() {
+        let options = by_class_options();
+        let alias = || Var::new("alias", Kind::Unresolved("SomeAlias".into()));
+        // `acmeFoo` is a local struct, which gets inlined into the page of `acme.Foo`
+        let foo = Class {
+            fields: vec![
+                Var::new("struct", Kind::Unresolved("acmeFoo".into())),
+                alias(),
+            ],
+            ..class("acme.Foo", &options)
+        };
+        let struct_ = Class {
+            fields: vec![alias()],
+            ..class("acmeFoo", &options)
+        };
+        let library = library(
+            vec![
+                Def::Class(foo),
+                Def::Class(struct_),
+                Def::Alias(Alias::new("SomeAlias", Kind::Lua(LuaKind::String))),
+            ],
+            &options,
+        );
+        let docs = library.export_docs(&options);
+        let (_, page) = docs.iter().find(|(name, _)| name == "acme/Foo").unwrap();
+        // anchors which slug to the same id get made unique, and links use the unique ones
+        assert!(page.contains("<a name=\"acmefoo\"></a>"));
+        assert!(page.contains("<a name=\"acmefoo-1\"></a>"));
+        assert!(page.contains("[`acmeFoo`](#acmefoo-1)"));
+        assert!(page.contains("<a name=\"acmefoo--alias-1\"></a>"));
+        // aliases which are used by the class and its structs get inlined once
+        assert_eq!(page.matches("### SomeAlias").count(), 1);
+        assert!(crate::generator::links::check_links(&docs, &[]).is_empty());
+    }
+
+    #[test]
+    fn globals_namespace_links() {
+        let options = by_class_options();
+        let some_class = Class {
+            desc: "Use `acme.utils.run()`.\nSee: [acme.utils](file:///library/acme/utils.lua#1)"
+                .to_string(),
+            ..class("acme.SomeClass", &options)
+        };
+        let library = library(
+            vec![
+                Def::Class(some_class),
+                Def::Function(Function {
+                    returns: vec![Var::unnamed(Kind::Unresolved("acme.SomeClass".into()))],
+                    ..Function::new("acme.utils.run")
+                }),
+            ],
+            &options,
+        );
+        // namespaces which only contain global functions are part of the symbol table
+        let utils = &library.classes["acme.utils"];
+        assert_eq!(utils.functions.len(), 1);
+        assert!(library.symbols.id("acme.utils").is_some());
+        assert!(matches!(utils.functions[0].returns[0].kind, Kind::Class(_)));
+        let docs = library.export_docs(&options);
+        let (_, page) = docs
+            .iter()
+            .find(|(name, _)| name == "acme/SomeClass")
+            .unwrap();
+        assert!(page.contains("[`acme.utils.run()`](../../API/acme/utils.md#acmeutils--run-fn)"));
+        assert!(page.contains("[`acme.utils`](../../API/acme/utils.md)"));
+        assert!(crate::generator::links::check_links(&docs, &[]).is_empty());
+    }
+
+    // create a synthetic library with many cross-referencing classes, functions and aliases
+    fn synthetic_defs(class_count: usize) -> Vec<Def> {
+        let var = |name: &str, kind: Kind| Var {
+            desc: Some("Some field description".to_string()),
+            ..Var::new(name, kind)
+        };
+        // even classes are global classes, odd ones local structs
+        let class_name = |i: usize| {
+            if i % 2 == 0 {
+                format!("acme.Class{}", i)
+            } else {
+                format!("Struct{}", i)
+            }
+        };
+        let mut defs = vec![];
+        for i in 0..class_count {
+            let name = class_name(i);
+            let other = |offset: usize| class_name((i + offset) % class_count);
+            defs.push(Def::Class(Class {
+                file: Some(format!("file{}.lua", i % 50).into()),
+                line_number: Some(i as u32),
+                fields: (0..10)
+                    .map(|f| var(&format!("field{}", f), Kind::Unresolved(other(f + 1))))
+                    .collect(),
+                functions: (0..5)
+                    .map(|f| Function {
+                        params: vec![
+                            var("self", Kind::SelfArg),
+                            var("a", Kind::Unresolved(other(f + 11))),
+                            var("b", Kind::Unresolved(format!("Alias{}", f))),
+                        ],
+                        returns: vec![var(
+                            "result",
+                            Kind::Array(Box::new(Kind::Unresolved(other(f + 17)))),
+                        )],
+                        desc: Some("Some function description".to_string()),
+                        ..Function::new(&format!("function{}", f))
+                    })
+                    .collect(),
+                desc: "Some class description".to_string(),
+                ..Class::new(&name, Scope::from_name(&name, &["acme".to_string()], &[]))
+            }));
+        }
+        for i in 0..class_count / 10 {
+            let kind = Kind::Enum(vec![
+                Kind::Lua(LuaKind::String),
+                Kind::Unresolved(format!("Struct{}", i * 2 + 1)),
+            ]);
+            defs.push(Def::Alias(Alias::new(&format!("Alias{}", i), kind)));
+        }
+        defs
+    }
+
+    // times resolving and rendering. to compare against the string-based lookup this replaced,
+    // copy this module onto the parent of the symbol table commit and run it on both revisions
+    // (release build, 20/40/80 classes):
+    //
+    //   string lookup:  resolve 28ms / 49ms / 92ms,  render 0.61s / 2.75s / 14.1s
+    //   symbol table:   resolve 1.4ms / 2.2ms / 3.4ms, render 0.59s / 2.20s / 7.98s
+    #[test]
+    #[ignore = "benchmark: run with `cargo test --release -- --ignored --nocapture`"]
+    fn benchmark_large_library() {
+        let options = Options {
+            order: OutputOrder::ByClass,
+            namespaces: vec!["acme".to_string()],
+            ..Default::default()
+        };
+        let filter = SymbolFilter::from_options(&options).unwrap();
+        let constant_rules = ConstantRules::from_options(&options).unwrap();
+        for class_count in [80, 160, 320] {
+            let defs = synthetic_defs(class_count);
+            let start = Instant::now();
+            let library = Library::from_defs(defs, &filter, &constant_rules, &options);
+            let resolve_time = start.elapsed();
+            let start = Instant::now();
+            let docs = library.export_docs(&options);
+            let render_time = start.elapsed();
+            println!(
+                "{} classes: resolve {:?}, render {:?} ({} pages)",
+                class_count,
+                resolve_time,
+                render_time,
+                docs.len()
+            );
+        }
+    }
+}
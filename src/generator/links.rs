@@ -0,0 +1,234 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::LazyLock,
+};
+
+use regex::Regex;
+
+// -------------------------------------------------------------------------------------------------
+
+// markdown link urls, e.g. `(../API/acme.md#anchor)` in `[text](../API/acme.md#anchor)`
+static LINK_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\]\(([^)\s]+)\)").unwrap());
+// anchors, as generated by the renderer
+static ANCHOR_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"<a name="([^"]*)">"#).unwrap());
+
+// -------------------------------------------------------------------------------------------------
+
+/// A relative link in a generated page, which points to a missing page or anchor.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BrokenLink {
+    /// path of the page which contains the link, relative to the book root
+    pub page: String,
+    pub url: String,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for BrokenLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({})", self.page, self.url, self.reason)
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// Check all relative links in the given API pages, as generated by `Library::export_docs`,
+/// and in the given TOC links of the book's SUMMARY.md.
+///
+/// Links to external urls are skipped. Links within fenced code blocks are ignored.
+pub(crate) fn check_links(docs: &[(String, String)], toc_links: &[String]) -> Vec<BrokenLink> {
+    let pages = docs
+        .iter()
+        .map(|(name, content)| {
+            let anchors = ANCHOR_RE
+                .captures_iter(content)
+                .map(|captures| captures[1].to_string())
+                .collect::<HashSet<_>>();
+            (page_path(name), anchors)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut broken = vec![];
+    let mut check = |page: &str, content: &str| {
+        for url in links(content) {
+            if url.contains("://") || url.starts_with("mailto:") {
+                continue;
+            }
+            let (path, anchor) = url.split_once('#').unwrap_or((&url, ""));
+            let target = if path.is_empty() {
+                page.to_string()
+            } else {
+                resolve(page, path)
+            };
+            let reason = match pages.get(&target) {
+                None => "missing page",
+                Some(anchors) if !anchor.is_empty() && !anchors.contains(anchor) => {
+                    "missing anchor"
+                }
+                _ => continue,
+            };
+            broken.push(BrokenLink {
+                page: page.to_string(),
+                url: url.clone(),
+                reason,
+            });
+        }
+    };
+    for (name, content) in docs {
+        check(&page_path(name), content);
+    }
+    check("SUMMARY.md", &toc_links.join("\n"));
+    broken
+}
+
+// path of the page with the given doc name, relative to the book root
+fn page_path(name: &str) -> String {
+    format!("API/{}.md", name)
+}
+
+// all link urls in the given markdown content, skipping fenced code blocks
+fn links(content: &str) -> Vec<String> {
+    let mut in_code_block = false;
+    let mut urls = vec![];
+    for line in content.lines() {
+        if line.trim_start_matches('>').trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        } else if !in_code_block {
+            urls.extend(
+                LINK_RE
+                    .captures_iter(line)
+                    .map(|captures| captures[1].to_string()),
+            );
+        }
+    }
+    urls
+}
+
+// resolve a relative url path from the given page, relative to the book root
+fn resolve(page: &str, path: &str) -> String {
+    let mut segments = page.split('/').collect::<Vec<_>>();
+    segments.pop();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn broken_links() {
+        let docs = vec![
+            (
+                "acme".to_string(),
+                "# acme<a name=\"acme\"></a>\n[`Foo`](acme/Foo.md#acmefoo) [`x`](#acme)\n\
+                 ```lua\n[1](ignored)\n```"
+                    .to_string(),
+            ),
+            (
+                "acme/Foo".to_string(),
+                "# Foo<a name=\"acmefoo\"></a>\n[`acme`](../../API/acme.md) \
+                 [`Bar`](../../API/acme/Bar.md) [`run`](#acmefoo-run) \
+                 [Lua](https://www.lua.org)"
+                    .to_string(),
+            ),
+        ];
+        let toc_links = vec![
+            "  - [acme](API/acme.md)".to_string(),
+            "  - [Baz](API/acme/Baz.md)".to_string(),
+        ];
+        let broken = check_links(&docs, &toc_links)
+            .iter()
+            .map(|link| link.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            broken,
+            vec![
+                "API/acme/Foo.md: ../../API/acme/Bar.md (missing page)",
+                "API/acme/Foo.md: #acmefoo-run (missing anchor)",
+                "SUMMARY.md: API/acme/Baz.md (missing page)",
+            ]
+        );
+    }
+
+    #[test]
+    fn valid_links() {
+        let docs = vec![
+            (
+                "acme".to_string(),
+                "# acme<a name=\"acme\"></a>\n[`Foo`](acme/Foo.md) \
+                 [`Foo.run`](./acme/Foo.md#acme-foo--run-fn) [`Bar`](../API/acme/Bar.md)"
+                    .to_string(),
+            ),
+            (
+                "acme/Foo".to_string(),
+                "# Foo<a name=\"acme-foo\"></a>\n### run<a name=\"acme-foo--run-fn\"></a>\n\
+                 [`acme`](../../API/acme.md#acme) [`Bar`](Bar.md#acme-bar)"
+                    .to_string(),
+            ),
+            (
+                "acme/Bar".to_string(),
+                "# Bar<a name=\"acme-bar\"></a>\n[`Foo`](../acme/Foo.md#acme-foo)".to_string(),
+            ),
+        ];
+        let toc_links = vec![
+            "  - [acme](API/acme.md)".to_string(),
+            "  - [Foo](API/acme/Foo.md)".to_string(),
+        ];
+        assert!(check_links(&docs, &toc_links).is_empty());
+        assert_eq!(
+            resolve("API/acme/Foo.md", "../../API/acme.md"),
+            "API/acme.md"
+        );
+        assert_eq!(resolve("API/acme.md", "./acme/Foo.md"), "API/acme/Foo.md");
+    }
+
+    #[test]
+    fn anchor_links() {
+        let docs = vec![(
+            "acme".to_string(),
+            "# acme<a name=\"acme\"></a>\n### run<a name=\"acme--run\"></a>\n\
+             [`acme`](#acme) [`run`](#acme--run) [`stop`](#acme--stop)"
+                .to_string(),
+        )];
+        let broken = check_links(&docs, &[])
+            .iter()
+            .map(|link| link.to_string())
+            .collect::<Vec<_>>();
+        // anchor-only links resolve against the page which contains them
+        assert_eq!(broken, vec!["API/acme.md: #acme--stop (missing anchor)"]);
+    }
+
+    #[test]
+    fn code_fences() {
+        let content = [
+            "[`a`](a.md)",
+            "```lua",
+            "local t = {}; t[1](b)",
+            "```",
+            "> ```",
+            "> [`c`](c.md)",
+            "> ```",
+            "[`d`](d.md#d)",
+        ]
+        .join("\n");
+        // links within fenced code blocks, also in quotes, are skipped
+        assert_eq!(links(&content), vec!["a.md", "d.md#d"]);
+        let docs = vec![("acme".to_string(), content)];
+        let broken = check_links(&docs, &[])
+            .iter()
+            .map(|link| link.url.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(broken, vec!["a.md", "d.md#d"]);
+    }
+}
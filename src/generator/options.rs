@@ -9,17 +9,175 @@ pub enum OutputOrder {
     /// Generate a markdown file for each **Lua source file** and only inline used aliases.
     ByFile,
     /// Generate a markdown file for each **Lua class** and inline all used local structs
-    /// and aliases into it. Requires a root namespace to be set too.
-    /// 
-    /// All classes which are not part of the root namespace will be added to a `modules`
+    /// and aliases into it. Requires at least one root namespace to be set too.
+    ///
+    /// Nested namespace tables such as `acme.sub.Thing` are written into sub directories,
+    /// e.g. `API/acme/sub/Thing.md`.
+    ///
+    /// All classes which are not part of a root namespace will be added to a `modules`
     /// file as globals.
     ByClass,
 }
 
+// -------------------------------------------------------------------------------------------------
+
+/// Lua runtime version, used to pick the standard library modules and manual links.
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum LuaVersion {
+    #[value(name = "5.1")]
+    Lua51,
+    #[value(name = "5.2")]
+    Lua52,
+    #[value(name = "5.3")]
+    Lua53,
+    #[value(name = "5.4")]
+    Lua54,
+    #[default]
+    #[value(name = "luajit")]
+    LuaJit,
+}
+
+impl LuaVersion {
+    /// Names of the standard library modules of this Lua version.
+    pub fn std_modules(&self) -> &'static [&'static str] {
+        const LUA_51: &[&str] = &[
+            "coroutine",
+            "debug",
+            "io",
+            "math",
+            "os",
+            "package",
+            "string",
+            "table",
+        ];
+        const LUA_52: &[&str] = &[
+            "bit32",
+            "coroutine",
+            "debug",
+            "io",
+            "math",
+            "os",
+            "package",
+            "string",
+            "table",
+        ];
+        const LUA_53: &[&str] = &[
+            "coroutine",
+            "debug",
+            "io",
+            "math",
+            "os",
+            "package",
+            "string",
+            "table",
+            "utf8",
+        ];
+        const LUA_JIT: &[&str] = &[
+            "bit",
+            "coroutine",
+            "debug",
+            "ffi",
+            "io",
+            "jit",
+            "math",
+            "os",
+            "package",
+            "string",
+            "table",
+        ];
+        match self {
+            LuaVersion::Lua51 => LUA_51,
+            LuaVersion::Lua52 => LUA_52,
+            LuaVersion::Lua53 | LuaVersion::Lua54 => LUA_53,
+            LuaVersion::LuaJit => LUA_JIT,
+        }
+    }
+
+    /// Name of this Lua version in the `Lua.runtime.version` setting of LuaLS.
+    pub fn luals_name(&self) -> &'static str {
+        match self {
+            LuaVersion::Lua51 => "Lua 5.1",
+            LuaVersion::Lua52 => "Lua 5.2",
+            LuaVersion::Lua53 => "Lua 5.3",
+            LuaVersion::Lua54 => "Lua 5.4",
+            LuaVersion::LuaJit => "LuaJIT",
+        }
+    }
+
+    /// Link to the upstream reference manual section of the given standard library module.
+    pub fn manual_url(&self, module: &str) -> Option<String> {
+        let (version, section) = match self {
+            LuaVersion::Lua51 | LuaVersion::LuaJit => {
+                if *self == LuaVersion::LuaJit {
+                    match module {
+                        "bit" => return Some("https://bitop.luajit.org/api.html".to_string()),
+                        "jit" => return Some("https://luajit.org/ext_jit.html".to_string()),
+                        "ffi" => return Some("https://luajit.org/ext_ffi_api.html".to_string()),
+                        _ => (),
+                    }
+                }
+                let section = match module {
+                    "coroutine" => "5.2",
+                    "package" => "5.3",
+                    "string" => "5.4",
+                    "table" => "5.5",
+                    "math" => "5.6",
+                    "io" => "5.7",
+                    "os" => "5.8",
+                    "debug" => "5.9",
+                    _ => return None,
+                };
+                ("5.1", section)
+            }
+            LuaVersion::Lua52 => {
+                let section = match module {
+                    "coroutine" => "6.2",
+                    "package" => "6.3",
+                    "string" => "6.4",
+                    "table" => "6.5",
+                    "math" => "6.6",
+                    "bit32" => "6.7",
+                    "io" => "6.8",
+                    "os" => "6.9",
+                    "debug" => "6.10",
+                    _ => return None,
+                };
+                ("5.2", section)
+            }
+            LuaVersion::Lua53 | LuaVersion::Lua54 => {
+                let section = match module {
+                    "coroutine" => "6.2",
+                    "package" => "6.3",
+                    "string" => "6.4",
+                    "utf8" => "6.5",
+                    "table" => "6.6",
+                    "math" => "6.7",
+                    "io" => "6.8",
+                    "os" => "6.9",
+                    "debug" => "6.10",
+                    _ => return None,
+                };
+                let version = if *self == LuaVersion::Lua53 {
+                    "5.3"
+                } else {
+                    "5.4"
+                };
+                (version, section)
+            }
+        };
+        Some(format!(
+            "https://www.lua.org/manual/{}/manual.html#{}",
+            version, section
+        ))
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 /// Options for the API doc generator.
 ///
 /// Includes clap argument definitions, when using the generator from the command line.
-#[derive(clap::Parser, Debug)]
+#[derive(clap::Parser, Debug, Clone)]
 #[command(version, about, long_about = None)]
 pub struct Options {
     /// LuaLS documented library source path.
@@ -31,11 +189,184 @@ pub struct Options {
     /// Classes which should be excluded from the docs.
     #[arg(name = "excluded_classes", value_parser, num_args = 0..)]
     pub excluded_classes: Vec<String>,
+    /// Symbol patterns which should be excluded from the docs.
+    ///
+    /// Patterns are globs (`*` and `?` wildcards) or regular expressions when prefixed
+    /// with `re:`. They match the short or fully qualified symbol name and can be restricted
+    /// to a symbol kind with a `class:`, `function:`, `field:`, `enum:` or `alias:` prefix,
+    /// e.g. `_*` or `class:acme.internal.*`. Constructors are matched as functions, which are
+    /// named like their class, operators as functions with their `@operator` name, e.g. `add`.
+    #[arg(name = "exclude", long, value_name = "PATTERN")]
+    pub excluded_symbols: Vec<String>,
+    /// Symbol patterns which should be included in the docs. When set, only symbols of the
+    /// pattern's kind that match at least one include pattern are documented. Uses the same
+    /// pattern syntax as `exclude`.
+    #[arg(name = "include", long, value_name = "PATTERN")]
+    pub included_symbols: Vec<String>,
+    /// Include members which are annotated as `@private`, `@protected` or `@package` in
+    /// the docs. Such members are rendered with a visibility badge.
+    #[arg(long)]
+    pub include_private: bool,
     /// The output structure of the docs.
     #[arg(name = "order", short, long, value_enum, default_value_t)]
     pub order: OutputOrder,
-    /// When set, use the given Lua table/class name as root namespace. 
-    /// This only applies when `order` is set to `"by-class"`.  
-    #[arg(name = "namespace", short, long, default_value = "")]
+    /// When set, use the given Lua table/class names as root namespaces. Each namespace
+    /// gets its own `API/<namespace>/` folder and TOC section.
+    /// This only applies when `order` is set to `"by-class"`.
+    #[arg(name = "namespace", short, long, value_delimiter = ',')]
+    pub namespaces: Vec<String>,
+    /// Single root namespace. Gets added to the `namespaces` when set.
+    #[deprecated(note = "use `namespaces` instead")]
+    #[arg(skip)]
     pub namespace: String,
+    /// Lua runtime version. Selects the standard library modules, which are documented as
+    /// "Module Extensions", and the Lua manual links on their pages.
+    /// When not set, the `bit`, `debug`, `ffi`, `io`, `jit`, `math`, `os` and `table` modules
+    /// are documented as module extensions, as in previous versions, and the Lua manual links
+    /// refer to LuaJIT.
+    #[arg(long, value_enum)]
+    pub lua_version: Option<LuaVersion>,
+    /// Standard library module names which should be documented as "Module Extensions".
+    /// When set, this replaces the module list of the selected `lua-version`.
+    #[arg(long, value_name = "MODULE", value_delimiter = ',')]
+    pub std_modules: Vec<String>,
+    /// Standard library modules, such as `string` or `table`, which should be documented
+    /// in full on their "Module Extensions" pages, using the annotated meta definitions
+    /// which ship with LuaLS. The Lua version of the meta definitions is the one that is
+    /// configured as `Lua.runtime.version` in the library's LuaLS config, so it should match
+    /// the selected `lua-version`. All modules must be part of the std module list.
+    #[arg(long, value_name = "MODULE", value_delimiter = ',')]
+    pub std_docs: Vec<String>,
+    /// Directory with markdown files which override or extend the builtin type pages.
+    /// A `<name>.md` file replaces the description of the builtin type `name`, or adds
+    /// a new builtin type page, when there's no such builtin type.
+    #[arg(long, value_name = "PATH")]
+    pub builtins_path: Option<PathBuf>,
+    /// Marker which removes a class, function, field, enum or alias from the docs, when
+    /// it is present in its description. Set to an empty string to disable the marker.
+    #[arg(long, value_name = "TAG", default_value = Options::DEFAULT_HIDDEN_TAG)]
+    pub hidden_tag: String,
+    /// Regular expression for field names which should be documented as constants.
+    /// Set to an empty string to disable name based constant detection.
+    #[arg(long, value_name = "REGEX", default_value = Options::DEFAULT_CONSTANT_PATTERN)]
+    pub constant_pattern: String,
+    /// Marker which documents a field as constant, when it is present in its description.
+    /// Set to an empty string to disable the marker.
+    #[arg(long, value_name = "TAG", default_value = Options::DEFAULT_CONSTANT_TAG)]
+    pub constant_tag: String,
+    /// Don't document fields with a literal `@type` value as constants.
+    #[arg(long)]
+    pub no_literal_constants: bool,
+    /// Render inline object types with more than the given number of fields as multi-line
+    /// field lists instead of a single line.
+    #[arg(long, value_name = "FIELDS")]
+    pub multiline_objects: Option<usize>,
+    /// Print how long each phase of the doc generation took.
+    #[arg(long)]
+    pub timings: bool,
+    /// Print additional details about the doc generation, such as fields which got
+    /// documented as constants or nested classes instead of properties.
+    #[arg(short, long)]
+    pub verbose: bool,
+}
+
+impl Options {
+    /// Standard library module names, as configured or as defined by the Lua version.
+    pub fn std_modules(&self) -> Vec<String> {
+        if !self.std_modules.is_empty() {
+            return self.std_modules.clone();
+        }
+        let std_modules = match self.lua_version {
+            Some(lua_version) => lua_version.std_modules(),
+            None => Self::DEFAULT_STD_MODULES,
+        };
+        std_modules.iter().map(|m| m.to_string()).collect()
+    }
+
+    /// Copy of the options with the deprecated `namespace` added to the `namespaces`.
+    #[allow(deprecated)]
+    pub(crate) fn merge_deprecated(&self) -> Self {
+        let mut options = self.clone();
+        if !self.namespace.is_empty() && !self.namespaces.contains(&self.namespace) {
+            options.namespaces.insert(0, self.namespace.clone());
+        }
+        options
+    }
+
+    pub const DEFAULT_STD_MODULES: &[&str] =
+        &["bit", "debug", "ffi", "io", "jit", "math", "os", "table"];
+    pub const DEFAULT_HIDDEN_TAG: &str = "@nodoc";
+    pub const DEFAULT_CONSTANT_PATTERN: &str = "^[A-Z_]+$";
+    pub const DEFAULT_CONSTANT_TAG: &str = "@constant";
+}
+
+#[allow(deprecated)]
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            library: PathBuf::new(),
+            output: PathBuf::new(),
+            excluded_classes: vec![],
+            excluded_symbols: vec![],
+            included_symbols: vec![],
+            include_private: false,
+            order: OutputOrder::default(),
+            namespaces: vec![],
+            namespace: String::new(),
+            lua_version: None,
+            std_modules: vec![],
+            std_docs: vec![],
+            builtins_path: None,
+            hidden_tag: Self::DEFAULT_HIDDEN_TAG.to_string(),
+            constant_pattern: Self::DEFAULT_CONSTANT_PATTERN.to_string(),
+            constant_tag: Self::DEFAULT_CONSTANT_TAG.to_string(),
+            no_literal_constants: false,
+            multiline_objects: None,
+            timings: false,
+            verbose: false,
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_namespace() {
+        let options = Options {
+            namespace: "acme".to_string(),
+            namespaces: vec!["other".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(options.merge_deprecated().namespaces, vec!["acme", "other"]);
+        let options = Options {
+            namespace: "acme".to_string(),
+            namespaces: vec!["acme".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(options.merge_deprecated().namespaces, vec!["acme"]);
+        assert!(Options::default().merge_deprecated().namespaces.is_empty());
+    }
+
+    #[test]
+    fn std_modules() {
+        // the module list of previous versions is used when no Lua version is set
+        let options = Options::default();
+        assert_eq!(options.std_modules(), Options::DEFAULT_STD_MODULES);
+        let options = Options {
+            lua_version: Some(LuaVersion::Lua51),
+            ..Default::default()
+        };
+        assert!(options.std_modules().contains(&"string".to_string()));
+        let options = Options {
+            lua_version: Some(LuaVersion::Lua51),
+            std_modules: vec!["table".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(options.std_modules(), vec!["table"]);
+    }
 }
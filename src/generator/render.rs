@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet},
     path::{Path, PathBuf},
+    sync::LazyLock,
 };
 
 use itertools::Itertools;
@@ -8,6 +9,7 @@ use regex::Regex;
 
 use crate::{
     generator::{
+        anchors::{slug, Anchors, PageAnchors, Target},
         library::Library,
         options::{Options, OutputOrder},
     },
@@ -16,6 +18,38 @@ use crate::{
 
 // -------------------------------------------------------------------------------------------------
 
+// a doc page, and the classes, structs and aliases it documents
+struct Page {
+    // path of the page, relative to the API folder
+    path: String,
+    // heading of pages which document multiple classes
+    title: Option<String>,
+    classes: Vec<Class>,
+    // names of the local classes and aliases which get inlined into the page
+    structs: Vec<String>,
+    aliases: Vec<String>,
+    render_toc: bool,
+}
+
+impl Page {
+    // assign anchors to everything the page documents, in the order it gets rendered
+    fn anchors(&self, library: &Library) -> PageAnchors {
+        let mut anchors = PageAnchors::default();
+        for class in &self.classes {
+            anchors.add_class(class);
+        }
+        for name in &self.structs {
+            anchors.add_class(&library.classes[name]);
+        }
+        for name in &self.aliases {
+            anchors.add(Target::alias(name));
+        }
+        anchors
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 impl Library {
     /// render each page inside the library as a list of string tuples (name, content)
     pub fn export_docs(&self, options: &Options) -> Vec<(String, String)> {
@@ -25,88 +59,120 @@ impl Library {
         match options.order {
             // split classes into globals and modules and organize by source file
             OutputOrder::ByFile => {
-                for (path, classes) in
-                    self.classes_by_file_in_scopes(&[Scope::Global, Scope::Local])
-                {
-                    if path.to_string_lossy().is_empty() {
-                        // skip classes which have no file path (Lua internals)
-                        continue;
-                    }
-                    let file_stem = path
-                        .file_stem()
-                        .map(|v| v.to_string_lossy())
-                        .expect("expecting class to have a valid source file path");
-
-                    let mut content = String::new();
-                    content.push_str(&h1(&file_stem));
-                    content.push_str("\n<!-- toc -->\n");
-
-                    for class in Self::sort_classes(classes) {
-                        let url_root = "../";
-                        let render_toc = false; // we already added a toc here
-                        content.push_str(&class.render(
-                            url_root,
-                            render_toc,
-                            &self.classes,
-                            &self.aliases,
-                            options,
-                        ));
-                        content.push_str("\n\n");
-                    }
-                    globals.push((file_stem.to_string(), content));
-                }
+                globals = self
+                    .classes_by_file_in_scopes(&[Scope::Global, Scope::Local])
+                    .into_iter()
+                    // skip classes which have no file path (Lua internals)
+                    .filter(|(path, _)| !path.to_string_lossy().is_empty())
+                    .map(|(path, classes)| {
+                        let file_stem = path
+                            .file_stem()
+                            .map(|v| v.to_string_lossy())
+                            .expect("expecting class to have a valid source file path");
+                        let classes = Self::sort_classes(classes);
+                        let render_toc = false; // we add a toc for the whole file instead
+                        let mut page = self.page(&file_stem, classes, render_toc, options);
+                        page.title = Some(file_stem.to_string());
+                        page
+                    })
+                    .collect();
 
-                for class in self.classes_in_scopes(&[Scope::Modules]) {
-                    let url_root = "../../";
-                    let render_toc = false;
-                    let content =
-                        class.render(url_root, render_toc, &self.classes, &self.aliases, options);
-                    modules.push((String::from("modules/") + &class.name, content));
-                }
+                modules = self
+                    .classes_in_scopes(&[Scope::Modules])
+                    .into_iter()
+                    .map(|class| {
+                        let page_path = String::from("modules/") + &class.name;
+                        let render_toc = false;
+                        self.page(&page_path, vec![class], render_toc, options)
+                    })
+                    .collect();
             }
-            // create separate files for each class in the root namespace
+            // create separate files for each class in the root namespaces, using sub
+            // directories for nested namespace tables
             OutputOrder::ByClass => {
-                for (class_name, class) in &self.classes {
-                    // url_root is the path to /API folder
-                    let url_root = if class_name == &options.namespace {
-                        "../" // namespace root
-                    } else {
-                        "../../" // namespace childs
-                    };
-                    let mut content = String::new();
-                    let render_toc = true;
-                    content.push_str(&class.render(
-                        url_root,
-                        render_toc,
-                        &self.classes,
-                        &self.aliases,
-                        options,
-                    ));
-                    match class.scope {
-                        Scope::Global => globals.push((class_name.clone(), content)),
-                        Scope::Modules => {
-                            modules.push(("modules/".to_string() + class_name, content))
-                        }
-                        Scope::Local => (),    // inlined in global classes
-                        Scope::Builtins => (), // handled separately below
-                    }
-                }
+                let render_toc = true;
+                globals = self
+                    .classes_in_scopes(&[Scope::Global])
+                    .into_iter()
+                    .map(|class| {
+                        let page_path = Class::page_path(&class.name);
+                        self.page(&page_path, vec![class], render_toc, options)
+                    })
+                    .collect();
+                modules = self
+                    .classes_in_scopes(&[Scope::Modules])
+                    .into_iter()
+                    .map(|class| {
+                        let page_path = "modules/".to_string() + &class.name;
+                        self.page(&page_path, vec![class], render_toc, options)
+                    })
+                    .collect();
             }
         }
 
+        // add modules which are loaded via require
+        let required = self
+            .classes
+            .values()
+            .filter(|c| matches!(c.scope, Scope::Require(_)))
+            .map(|class| {
+                let page_path = "require/".to_string() + &class.scope.page_name(&class.name);
+                let render_toc = options.order == OutputOrder::ByClass;
+                self.page(&page_path, vec![class.clone()], render_toc, options)
+            })
+            .collect::<Vec<_>>();
+
         // add builtin classes
-        let mut builtins = vec![];
-        for class in Library::builtin_classes() {
-            let url_root = "../../";
-            let render_toc = false;
-            let content = class.render(url_root, render_toc, &self.classes, &self.aliases, options);
-            builtins.push((String::from("builtins/") + &class.name, content));
+        let referenced_builtins = self.referenced_builtins();
+        let builtins = self
+            .builtins
+            .iter()
+            .filter(|c| referenced_builtins.contains(&c.name))
+            .map(|class| {
+                let page_path = String::from("builtins/") + &class.name;
+                let render_toc = false;
+                self.page(&page_path, vec![class.clone()], render_toc, options)
+            })
+            .collect::<Vec<_>>();
+
+        // assign anchors of all pages first, so links can refer to anchors on other pages
+        let page_anchors = [&globals, &required, &modules, &builtins]
+            .into_iter()
+            .flatten()
+            .map(|page| (page.path.clone(), page.anchors(self)))
+            .collect::<HashMap<_, _>>();
+        let anchors = Anchors {
+            pages: &page_anchors,
+            page: "",
+        };
+        let render = |page: Page| {
+            let content = self.render_page(&page, anchors.for_page(&page.path), options);
+            (page.path, content)
+        };
+
+        let global_paths = globals
+            .iter()
+            .map(|page| page.path.clone())
+            .collect::<HashSet<_>>();
+        let mut globals = render_pages(globals, render);
+        let mut required = render_pages(required, render);
+        let mut modules = render_pages(modules, render);
+        let mut builtins = render_pages(builtins, render);
+
+        // add index pages for nested namespace tables which have no class
+        if options.order == OutputOrder::ByClass {
+            globals.append(&mut Self::namespace_index_pages(&global_paths));
         }
 
         // create final docs
         let mut docs: Vec<(String, String)> = vec![];
         docs.append(&mut globals);
 
+        if !required.is_empty() {
+            docs.push(("require".to_string(), "# Lua Modules".to_string()));
+            docs.append(&mut required);
+        }
+
         if !modules.is_empty() {
             docs.push(("modules".to_string(), "# Lua Module Extensions".to_string()));
             docs.append(&mut modules);
@@ -123,6 +189,189 @@ impl Library {
         Self::sort_docs(docs)
     }
 
+    // create a page with the given path for the given classes, inlining the local classes and
+    // aliases they use
+    fn page(&self, path: &str, classes: Vec<Class>, render_toc: bool, options: &Options) -> Page {
+        let symbols = &self.symbols;
+        let mut struct_names = HashSet::new();
+        let mut alias_names = HashSet::new();
+        for class in &classes {
+            match options.order {
+                // when organizing by files, inline used aliases only: local classes are
+                // rendered into the pages of their files
+                OutputOrder::ByFile => {
+                    alias_names.extend(self.dependencies.local_aliases(class, symbols));
+                }
+                // when organizing by class, inline everything the class refers to
+                OutputOrder::ByClass => {
+                    let (structs, aliases) = self.dependencies.local_types(class, symbols);
+                    struct_names.extend(structs);
+                    alias_names.extend(aliases);
+                }
+            }
+        }
+        Page {
+            path: path.to_string(),
+            title: None,
+            classes,
+            structs: struct_names
+                .into_iter()
+                .filter(|name| self.classes.contains_key(name))
+                .sorted()
+                .collect(),
+            aliases: alias_names
+                .into_iter()
+                .filter(|name| self.aliases.contains_key(name))
+                .sorted()
+                .collect(),
+            render_toc,
+        }
+    }
+
+    // render the classes of the given page, followed by the structs and aliases they use
+    fn render_page(&self, page: &Page, anchors: Anchors, options: &Options) -> String {
+        let url_root = url_root(&page.path);
+        let mut content = vec![];
+        if let Some(title) = &page.title {
+            content.push(format!("{}\n<!-- toc -->\n", h1(title)));
+        }
+        let classes = page
+            .classes
+            .iter()
+            .map(|class| class.render(&url_root, page.render_toc, self, options, anchors))
+            .collect::<Vec<_>>();
+        content.push(classes.join("\n\n"));
+
+        // append all used local classes (structs)
+        if !page.structs.is_empty() {
+            content.push("\n\n\n---".to_string());
+            content.push(h2("Structs"));
+            for name in &page.structs {
+                let render_toc = false;
+                let struct_ = &self.classes[name];
+                content.push(struct_.render(&url_root, render_toc, self, options, anchors));
+            }
+        }
+
+        // append all used local aliases
+        if !page.aliases.is_empty() {
+            content.push("\n\n\n---".to_string());
+            content.push(h2("Aliases"));
+            for name in &page.aliases {
+                let alias = &self.aliases[name];
+                let file = alias.file.clone().unwrap_or_default();
+                content.push(alias.render(&url_root, &file, &self.symbols, options, anchors));
+                content.push(String::new());
+            }
+        }
+
+        content.push("\n".to_string());
+        link_descriptions(&content.join("  \n"), &url_root, self, options, anchors)
+    }
+
+    // create pages for all parent namespaces of the given page paths which have no page,
+    // listing their child pages
+    fn namespace_index_pages(page_paths: &HashSet<String>) -> Vec<(String, String)> {
+        let mut children = HashMap::<String, Vec<String>>::new();
+        for page_path in page_paths {
+            let mut path = page_path.as_str();
+            while let Some(pos) = path.rfind('/') {
+                let parent = &path[..pos];
+                children
+                    .entry(parent.to_string())
+                    .or_default()
+                    .push(path.to_string());
+                path = parent;
+            }
+        }
+        children
+            .into_iter()
+            .filter(|(parent, _)| !page_paths.contains(parent))
+            .map(|(parent, child_paths)| {
+                let name = parent.replace('/', ".");
+                let url_root = url_root(&parent);
+                let mut content = vec![h1(&hash(&name, &slug(&name)))];
+                content.push(String::new());
+                for child_path in child_paths.into_iter().unique().sorted() {
+                    let child_name = child_path.replace('/', ".");
+                    content.push(format!(
+                        "- {}",
+                        file_link(&child_name, &(url_root.clone() + "API/" + &child_path))
+                    ));
+                }
+                (parent, content.join("\n"))
+            })
+            .collect()
+    }
+
+    // the class, enum, alias or class member the given code span text refers to, as symbol kind and
+    // anchor target of the member
+    pub(super) fn description_target(&self, text: &str) -> Option<(Kind, Option<Target>)> {
+        if !SYMBOL_NAME_RE.is_match(text) {
+            return None;
+        }
+        let name = text.strip_suffix("()").unwrap_or(text);
+        // don't link builtin types to module classes with the same name
+        if serde_json::from_str::<LuaKind>(&format!("\"{}\"", name)).is_ok() {
+            return None;
+        }
+        let symbols = &self.symbols;
+        match symbols.resolve_string(name) {
+            Some(kind @ (Kind::Class(_) | Kind::EnumRef(_) | Kind::Alias(_))) => Some((kind, None)),
+            Some(_) => None,
+            None => {
+                let pos = name.rfind(['.', ':'])?;
+                let (base, member) = (&name[..pos], &name[pos + 1..]);
+                let class = self.classes.get(base)?;
+                let is_value = class
+                    .fields
+                    .iter()
+                    .chain(&class.constants)
+                    .any(|v| v.name.as_deref() == Some(member))
+                    || class
+                        .enums
+                        .iter()
+                        .any(|e| Class::get_end(&e.name).unwrap_or(&e.name) == member);
+                let is_function = class
+                    .functions
+                    .iter()
+                    .any(|f| f.name.as_deref() == Some(member));
+                if !(is_value || is_function) {
+                    return None;
+                }
+                // `Class:member` and `Class.member()` refer to the function when there's a field
+                // with the same name too
+                let is_call = name.contains(':') || text.ends_with("()");
+                let target = if is_function && (is_call || !is_value) {
+                    Target::function(base, member)
+                } else {
+                    Target::member(base, member)
+                };
+                Some((Kind::Class(symbols.id(base)?), Some(target)))
+            }
+        }
+    }
+
+    // link to the class, enum, alias or class member the given code span text refers to. local
+    // classes and aliases are only linked on the pages they are inlined into.
+    fn description_link(
+        &self,
+        text: &str,
+        url_root: &str,
+        options: &Options,
+        anchors: Anchors,
+    ) -> Option<String> {
+        let symbols = &self.symbols;
+        let url = match self.description_target(text)? {
+            (Kind::Class(id), Some(member)) => {
+                let page = class_page(symbols.get(id), options);
+                anchor_url(page.as_deref(), &member, url_root, anchors)
+            }
+            (kind, _) => kind.url(url_root, symbols, options, anchors),
+        };
+        url.map(|url| format!("[`{}`]({})", text, url))
+    }
+
     fn classes_in_scopes(&self, scopes: &[Scope]) -> Vec<Class> {
         self.classes
             .values()
@@ -131,15 +380,19 @@ impl Library {
             .collect()
     }
 
+    // classes which are defined across multiple files are added to the class's file and as
+    // partial classes with the members of the other files to each other file.
     fn classes_by_file_in_scopes(&self, scopes: &[Scope]) -> HashMap<PathBuf, Vec<Class>> {
         let mut map = HashMap::<PathBuf, Vec<Class>>::new();
         for class in self.classes_in_scopes(scopes) {
             let file = class.file.clone().unwrap_or_default();
-            if let Some(classes) = map.get_mut(&file) {
-                classes.push(class.clone());
-            } else {
-                map.insert(file.clone(), vec![class.clone()]);
+            for other_file in class.files().iter().filter(|f| **f != file) {
+                let partial = class.partial(other_file);
+                if !partial.is_empty() || !partial.constants.is_empty() {
+                    map.entry(other_file.clone()).or_default().push(partial);
+                }
             }
+            map.entry(file).or_default().push(class);
         }
         map
     }
@@ -160,6 +413,8 @@ impl Library {
         let custom_weight = |name: &str| -> usize {
             if name == "global" {
                 0
+            } else if name.starts_with("require") {
+                50
             } else if name.starts_with("modules") {
                 99
             } else if name.starts_with("builtins") {
@@ -168,13 +423,109 @@ impl Library {
                 10
             }
         };
-        docs.sort_by_key(|(name, _)| (custom_weight(name), name.to_lowercase()));
+        // sort by path segments, so nested pages follow their parent pages
+        docs.sort_by_key(|(name, _)| {
+            (
+                custom_weight(name),
+                name.to_lowercase()
+                    .split('/')
+                    .map(String::from)
+                    .collect::<Vec<_>>(),
+            )
+        });
         docs
     }
 }
 
 // -------------------------------------------------------------------------------------------------
 
+// render the given items into doc pages, in parallel when the `parallel` feature is enabled
+fn render_pages<T, F>(items: Vec<T>, render: F) -> Vec<(String, String)>
+where
+    T: Send,
+    F: Fn(T) -> (String, String) + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items.into_par_iter().map(render).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.into_iter().map(render).collect()
+    }
+}
+
+// relative path from the page at the given path (within the API folder) to the book root
+fn url_root(page_path: &str) -> String {
+    "../".repeat(page_path.matches('/').count() + 1)
+}
+
+// path of the page which documents the given class symbol, relative to the API folder. local
+// classes have no page when organizing by class: they get inlined into the pages using them.
+fn class_page(class: &Symbol, options: &Options) -> Option<String> {
+    match class.scope {
+        Scope::Local | Scope::Global => match options.order {
+            OutputOrder::ByFile => Some(file_page(class.file.as_deref())),
+            OutputOrder::ByClass => {
+                (class.scope == Scope::Global).then(|| Class::page_path(&class.name))
+            }
+        },
+        _ => Some(
+            class
+                .scope
+                .path_prefix()
+                .trim_start_matches("API/")
+                .to_string()
+                + &class.scope.page_name(&class.name),
+        ),
+    }
+}
+
+// path of the page which documents the classes of the given file when organizing by file
+fn file_page(file: Option<&Path>) -> String {
+    file.and_then(Path::file_stem)
+        .map(|v| v.to_string_lossy().to_string())
+        .unwrap_or("[unknown file]".into())
+}
+
+// url of the anchor of the given target on the page with the given path, or on the page which
+// gets rendered when there's no path. None when the page has no such anchor.
+fn anchor_url(
+    page: Option<&str>,
+    target: &Target,
+    url_root: &str,
+    anchors: Anchors,
+) -> Option<String> {
+    match page {
+        Some(page) => anchors
+            .get_on(page, target)
+            .map(|anchor| format!("{}API/{}.md#{}", url_root, page, anchor)),
+        None => anchors.get(target).map(|anchor| format!("#{}", anchor)),
+    }
+}
+
+// url of the page or anchor which documents the given class symbol
+fn class_url(
+    class: &Symbol,
+    url_root: &str,
+    options: &Options,
+    anchors: Anchors,
+) -> Option<String> {
+    let target = Target::class(&class.name);
+    match class_page(class, options) {
+        // file pages document multiple classes
+        Some(page)
+            if options.order == OutputOrder::ByFile
+                && matches!(class.scope, Scope::Local | Scope::Global) =>
+        {
+            anchor_url(Some(&page), &target, url_root, anchors)
+        }
+        Some(page) => Some(format!("{}API/{}.md", url_root, page)),
+        None => anchor_url(None, &target, url_root, anchors),
+    }
+}
+
 fn heading(text: &str, level: usize) -> String {
     format!("{} {}", "#".repeat(level), text)
 }
@@ -195,35 +546,85 @@ fn file_link(text: &str, url: &str) -> String {
     format!("[`{}`]({}.md)", text, url)
 }
 
-fn class_link(text: &str, url: &str, hash: &str) -> String {
-    format!("[`{}`]({}.md#{})", text, url, hash)
+// link to the given url with the text as code span, or the code span only without url
+fn code_link(text: &str, url: Option<String>) -> String {
+    match url {
+        Some(url) => format!("[`{}`]({})", text, url),
+        None => format!("`{}`", text),
+    }
 }
 
-fn local_class_link(text: &str, hash: &str) -> String {
-    format!("[`{}`](#{})", text, hash.to_lowercase())
+fn quote(text: &str) -> String {
+    format!("> {}", text.replace('\n', "\n> "))
 }
 
-fn enum_link(text: &str, url: &str, hash: &str) -> String {
-    format!("[`{}`]({}.md#{})", text, url, hash)
-}
+// regular expressions used to rewrite descriptions, compiled once
+static FILE_LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[([^\]]+)\]\(file://[^\)]*\)").unwrap());
+static MANUAL_LINK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\(command:extension\.lua\.doc\?\["[^/"]+/(\d)(\d)/manual\.html/([^"]+)"\]\)"#)
+        .unwrap()
+});
+static COMMAND_LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[([^\]]+)\]\(command:[^\)]*\)").unwrap());
 
-fn alias_link(text: &str, hash: &str) -> String {
-    format!("[`{}`](#{})", text, hash)
-}
+// code spans in descriptions, which may refer to symbols of the library
+static CODE_SPAN_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(`+)([^`]+)(`+)").unwrap());
+// symbol names and references to class members such as `Class.function` or `Class:method()`
+static SYMBOL_NAME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z_]\w*(?:[.:][A-Za-z_]\w*)*(?:\(\))?$").unwrap());
 
-fn quote(text: &str) -> String {
-    format!("> {}", text.replace('\n', "\n> "))
+// link code spans in descriptions which refer to classes, enums or class members of the library.
+// code spans in double backticks, e.g. ``acme.SomeClass``, are not linked.
+fn link_descriptions(
+    content: &str,
+    url_root: &str,
+    library: &Library,
+    options: &Options,
+    anchors: Anchors,
+) -> String {
+    let mut in_code_block = false;
+    content
+        .split('\n')
+        .map(|line| {
+            let Some(text) = line.strip_prefix('>') else {
+                return line.to_string();
+            };
+            if text.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+            }
+            if in_code_block {
+                return line.to_string();
+            }
+            CODE_SPAN_RE
+                .replace_all(line, |captures: &regex::Captures| {
+                    let span = captures.get(0).unwrap();
+                    let is_link_text = line[..span.start()].ends_with('[');
+                    if captures[1].len() != 1 || captures[3].len() != 1 || is_link_text {
+                        return span.as_str().to_string();
+                    }
+                    library
+                        .description_link(&captures[2], url_root, options, anchors)
+                        .unwrap_or(span.as_str().to_string())
+                })
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 fn description(desc: &str) -> String {
     if desc.is_empty() {
         String::new()
     } else {
-        // remove file markdown file links from @see annotations
-        let file_link_re = Regex::new(r"\[([^\]]+)\]\(file://[^\)]*\)").unwrap();
-        // TODO: such links could in theory be resolved and rewritten as internal links while
-        // generating the library...
-        let desc = file_link_re.replace_all(desc, "`$1`");
+        // remove remaining markdown links to the Lua source files. `@see` references already
+        // got rewritten by the library, see `Library::resolve_see_references`.
+        let desc = FILE_LINK_RE.replace_all(desc, "`$1`");
+        // rewrite manual links from LuaLS std meta files to the online Lua manual
+        let desc =
+            MANUAL_LINK_RE.replace_all(&desc, "(https://www.lua.org/manual/$1.$2/manual.html#$3)");
+        // remove all other editor command links
+        let desc = COMMAND_LINK_RE.replace_all(&desc, "$1");
         // add one more h level for examples
         let desc = desc.replace("### examples", "#### examples");
         // remove leading and trailing newlines
@@ -232,8 +633,16 @@ fn description(desc: &str) -> String {
     }
 }
 
-fn hash(text: &str, hash: &str) -> String {
-    format!("{}<a name=\"{}\"></a>", text, hash)
+fn hash(text: &str, anchor: &str) -> String {
+    format!("{}<a name=\"{}\"></a>", text, anchor)
+}
+
+fn visibility_badge(visibility: Visibility) -> String {
+    if visibility.is_public() {
+        String::new()
+    } else {
+        format!(" <sup>{}</sup>", visibility.show())
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -248,132 +657,166 @@ impl LuaKind {
 // -------------------------------------------------------------------------------------------------
 
 impl Kind {
-    fn link(&self, url_root: &str, file: &Path, options: &Options) -> String {
+    fn link(
+        &self,
+        url_root: &str,
+        file: &Path,
+        symbols: &SymbolTable,
+        options: &Options,
+        anchors: Anchors,
+    ) -> String {
         match self {
             Kind::Lua(lk) => lk.link(url_root),
             Kind::Literal(k, s) => match k.as_ref() {
                 LuaKind::String => format!("`\"{}\"`", s),
-                LuaKind::Integer | LuaKind::Number => format!("`{}`", s.clone()),
+                LuaKind::Integer | LuaKind::Number | LuaKind::Boolean => format!("`{}`", s),
                 _ => s.clone(),
             },
-            Kind::Class(class) => match class.scope {
-                Scope::Local | Scope::Global => match options.order {
-                    OutputOrder::ByFile => {
-                        let file = class.file.clone().unwrap_or_default();
-                        let file_stem = file
-                            .file_stem()
-                            .map(|v| v.to_string_lossy())
-                            .unwrap_or("[unknown file]".into());
-                        class_link(
-                            &class.name,
-                            &(url_root.to_string()
-                                + &class.scope.path_prefix(&options.namespace)
-                                + &file_stem),
-                            &class.name,
-                        )
-                    }
-                    OutputOrder::ByClass => {
-                        if class.scope == Scope::Local {
-                            local_class_link(&class.name, &class.name)
-                        } else {
-                            file_link(
-                                &class.name,
-                                &(url_root.to_string()
-                                    + &class.scope.path_prefix(&options.namespace)
-                                    + &class.name),
-                            )
-                        }
-                    }
-                },
-                _ => file_link(
-                    &class.name,
-                    &(url_root.to_string()
-                        + &class.scope.path_prefix(&options.namespace)
-                        + &class.name),
-                ),
-            },
+            Kind::Class(id) | Kind::EnumRef(id) | Kind::Alias(id) => code_link(
+                symbols.name(*id),
+                self.url(url_root, symbols, options, anchors),
+            ),
             Kind::Enum(kinds) => kinds
                 .iter()
-                .map(|k| k.link(url_root, file, options))
+                .map(|k| k.link(url_root, file, symbols, options, anchors))
                 .collect::<Vec<String>>()
                 .join(" | "),
-            Kind::EnumRef(enumref) => match options.order {
-                OutputOrder::ByFile => {
-                    let file = enumref.file.clone().unwrap_or(PathBuf::new());
-                    let file_stem = file
-                        .file_stem()
-                        .map(|v| v.to_string_lossy())
-                        .unwrap_or("[unknown file]".into());
-                    enum_link(
-                        &enumref.name,
-                        &(url_root.to_string()
-                            + &Scope::Global.path_prefix(&options.namespace)
-                            + &file_stem),
-                        &enumref.name,
-                    )
-                }
-                OutputOrder::ByClass => enum_link(
-                    &enumref.name,
-                    Class::get_base(&enumref.name).unwrap_or(&enumref.name),
-                    Class::get_end(&enumref.name).unwrap_or_default(),
-                ),
-            },
+            // escape brackets, so they don't get parsed as markdown link text
+            Kind::Tuple(kinds) => format!(
+                "\\[{}\\]",
+                kinds
+                    .iter()
+                    .map(|k| k.link(url_root, file, symbols, options, anchors))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
             Kind::SelfArg => format!("[*self*]({}API/builtins/self.md)", url_root),
-            Kind::Array(k) => format!("{}[]", k.link(url_root, file, options)),
+            Kind::Array(k) => format!("{}[]", k.link(url_root, file, symbols, options, anchors)),
             Kind::Nullable(k) => format!(
                 "{}{}",
-                k.as_ref().link(url_root, file, options),
+                k.as_ref().link(url_root, file, symbols, options, anchors),
                 file_link("?", &format!("{}API/builtins/nil", url_root))
             ),
-            Kind::Alias(alias) => alias_link(&alias.name, &alias.name),
-            Kind::Function(f) => f.short(url_root, file, options),
+            Kind::Function(f) => f.short(url_root, file, symbols, options, anchors),
             Kind::Table(k, v) => format!(
                 "table<{}, {}>",
-                k.as_ref().link(url_root, file, options),
-                v.as_ref().link(url_root, file, options)
+                k.as_ref().link(url_root, file, symbols, options, anchors),
+                v.as_ref().link(url_root, file, symbols, options, anchors)
             ),
-            Kind::Object(hm) => {
-                let mut keys = hm.iter().map(|(k, _)| k.clone()).collect::<Vec<String>>();
-                keys.sort();
-                let fields = keys
+            Kind::Object(fields) => {
+                let fields = fields
                     .iter()
-                    .map(|k| {
+                    .map(|(key, kind)| {
                         format!(
                             "{} : {}",
-                            k,
-                            hm.get(k).unwrap().link(url_root, file, options)
+                            key,
+                            kind.link(url_root, file, symbols, options, anchors)
                         )
                     })
-                    .collect::<Vec<String>>()
-                    .join(", "); // TODO print on newlines?
-                format!("{{ {} }}", fields)
+                    .collect::<Vec<String>>();
+                if options
+                    .multiline_objects
+                    .is_some_and(|max_fields| fields.len() > max_fields)
+                {
+                    // indent nested multi-line objects along with the fields
+                    let indent = "&nbsp;&nbsp;&nbsp;&nbsp;";
+                    let fields = fields
+                        .iter()
+                        .map(|f| {
+                            indent.to_string() + &f.replace("<br>", &("<br>".to_string() + indent))
+                        })
+                        .collect::<Vec<String>>()
+                        .join(",<br>");
+                    format!("{{<br>{}<br>}}", fields)
+                } else {
+                    format!("{{ {} }}", fields.join(", "))
+                }
+            }
+            Kind::Variadic(k) => {
+                format!("...{}", k.link(url_root, file, symbols, options, anchors))
             }
-            Kind::Variadic(k) => format!("...{}", k.link(url_root, file, options)),
             Kind::Unresolved(s) => s.clone(),
         }
     }
+
+    // url of the page or anchor which documents the class, enum or alias this kind refers to.
+    // None for all other kinds and for types which are not documented on any page.
+    fn url(
+        &self,
+        url_root: &str,
+        symbols: &SymbolTable,
+        options: &Options,
+        anchors: Anchors,
+    ) -> Option<String> {
+        match self {
+            Kind::Class(id) => class_url(symbols.get(*id), url_root, options, anchors),
+            Kind::EnumRef(id) => {
+                let enumref = symbols.get(*id);
+                let base = Class::get_base(&enumref.name).unwrap_or("global");
+                let end = Class::get_end(&enumref.name).unwrap_or(&enumref.name);
+                // enums are documented as members of their base class
+                let page = match symbols.id(base).map(|id| symbols.get(id)) {
+                    Some(class) if class.symbol_type == SymbolType::Class => {
+                        class_page(class, options)
+                    }
+                    _ => Some(match options.order {
+                        OutputOrder::ByFile => file_page(enumref.file.as_deref()),
+                        OutputOrder::ByClass => Class::page_path(base),
+                    }),
+                };
+                let target = Target::member(base, end);
+                anchor_url(page.as_deref(), &target, url_root, anchors)
+            }
+            // aliases are inlined into the pages which use them
+            Kind::Alias(id) => {
+                let target = Target::alias(symbols.name(*id));
+                anchor_url(None, &target, url_root, anchors)
+            }
+            _ => None,
+        }
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
 
 impl Var {
-    fn short(&self, url_root: &str, file: &Path, options: &Options) -> String {
+    fn short(
+        &self,
+        url_root: &str,
+        file: &Path,
+        symbols: &SymbolTable,
+        options: &Options,
+        anchors: Anchors,
+    ) -> String {
         if matches!(self.kind, Kind::SelfArg) {
-            self.kind.link(url_root, file, options)
+            self.kind.link(url_root, file, symbols, options, anchors)
         } else if let Some(name) = self.name.clone() {
-            format!("{} : {}", name, self.kind.link(url_root, file, options))
+            format!(
+                "{} : {}",
+                name,
+                self.kind.link(url_root, file, symbols, options, anchors)
+            )
         } else {
-            self.kind.link(url_root, file, options)
+            self.kind.link(url_root, file, symbols, options, anchors)
         }
     }
 
-    fn long(&self, url_root: &str, file: &Path, options: &Options) -> String {
+    fn long(
+        &self,
+        anchor: &str,
+        url_root: &str,
+        file: &Path,
+        symbols: &SymbolTable,
+        options: &Options,
+        anchors: Anchors,
+    ) -> String {
         let desc = self.desc.clone().unwrap_or_default();
         format!(
             "{}{}",
             hash(
-                &h3(&self.short(url_root, file, options)),
-                &self.name.clone().unwrap_or_default()
+                &(h3(&self.short(url_root, file, symbols, options, anchors))
+                    + &visibility_badge(self.visibility)),
+                anchor
             ),
             if desc.is_empty() {
                 desc
@@ -387,11 +830,18 @@ impl Var {
 // -------------------------------------------------------------------------------------------------
 
 impl Alias {
-    fn render(&self, url_root: &str, file: &Path, options: &Options) -> String {
+    fn render(
+        &self,
+        url_root: &str,
+        file: &Path,
+        symbols: &SymbolTable,
+        options: &Options,
+        anchors: Anchors,
+    ) -> String {
         format!(
             "{}\n{}  \n{}",
-            hash(&h3(&self.name), &self.name),
-            self.kind.link(url_root, file, options),
+            hash(&h3(&self.name), &anchors.anchor(&Target::alias(&self.name))),
+            self.kind.link(url_root, file, symbols, options, anchors),
             self.desc
                 .clone()
                 .map(|d| description(d.as_str()))
@@ -403,36 +853,59 @@ impl Alias {
 // -------------------------------------------------------------------------------------------------
 
 impl Function {
-    fn long(&self, url_root: &str, file: &Path, options: &Options) -> String {
+    fn long(
+        &self,
+        anchor: &str,
+        url_root: &str,
+        file: &Path,
+        symbols: &SymbolTable,
+        options: &Options,
+        anchors: Anchors,
+    ) -> String {
         let name = self.name.clone().unwrap_or("fun".to_string());
         if self.params.is_empty() {
-            let name = hash(&h3(&format!("`{}()`", &name)), &name);
-            self.with_desc(&self.with_returns(&name, url_root, file, options))
+            let name = hash(
+                &(h3(&format!("`{}()`", &name)) + &visibility_badge(self.visibility)),
+                anchor,
+            );
+            self.with_desc(&self.with_returns(&name, url_root, file, symbols, options, anchors))
         } else {
             let params = self
                 .params
                 .iter()
-                .map(|v| v.short(url_root, file, options))
+                .map(|v| v.short(url_root, file, symbols, options, anchors))
                 .collect::<Vec<String>>()
                 .join(", ");
 
             self.with_desc(&self.with_returns(
-                &hash(&format!("### {}({})", &name, params), &name),
+                &hash(
+                    &(format!("### {}({})", &name, params) + &visibility_badge(self.visibility)),
+                    anchor,
+                ),
                 url_root,
                 file,
+                symbols,
                 options,
+                anchors,
             ))
         }
     }
-    fn short(&self, url_root: &str, file: &Path, options: &Options) -> String {
+    fn short(
+        &self,
+        url_root: &str,
+        file: &Path,
+        symbols: &SymbolTable,
+        options: &Options,
+        anchors: Anchors,
+    ) -> String {
         if self.params.is_empty() && self.returns.is_empty() {
             return self.empty();
         }
-        let returns = Self::render_vars(&self.returns, url_root, file, options);
+        let returns = Self::render_vars(&self.returns, url_root, file, symbols, options, anchors);
         format!(
             "{}({}){}",
             &self.name.clone().unwrap_or_default(),
-            Self::render_vars(&self.params, url_root, file, options),
+            Self::render_vars(&self.params, url_root, file, symbols, options, anchors),
             if returns.is_empty() {
                 returns
             } else {
@@ -443,9 +916,16 @@ impl Function {
     fn empty(&self) -> String {
         format!("{}()", &self.name.clone().unwrap_or("fun".to_string()))
     }
-    fn render_vars(vars: &[Var], url_root: &str, file: &Path, options: &Options) -> String {
+    fn render_vars(
+        vars: &[Var],
+        url_root: &str,
+        file: &Path,
+        symbols: &SymbolTable,
+        options: &Options,
+        anchors: Anchors,
+    ) -> String {
         vars.iter()
-            .map(|v| v.short(url_root, file, options))
+            .map(|v| v.short(url_root, file, symbols, options, anchors))
             .collect::<Vec<String>>()
             .join(", ")
     }
@@ -457,11 +937,19 @@ impl Function {
             format!("{}\n{}", head, description(&desc))
         }
     }
-    fn with_returns(&self, head: &str, url_root: &str, file: &Path, options: &Options) -> String {
+    fn with_returns(
+        &self,
+        head: &str,
+        url_root: &str,
+        file: &Path,
+        symbols: &SymbolTable,
+        options: &Options,
+        anchors: Anchors,
+    ) -> String {
         let returns = self
             .returns
             .iter()
-            .map(|v| v.short(url_root, file, options))
+            .map(|v| v.short(url_root, file, symbols, options, anchors))
             .collect::<Vec<String>>()
             .join(", ");
         if returns.is_empty() {
@@ -474,15 +962,58 @@ impl Function {
 
 // -------------------------------------------------------------------------------------------------
 
+impl Operator {
+    fn long(
+        &self,
+        anchor: &str,
+        url_root: &str,
+        file: &Path,
+        symbols: &SymbolTable,
+        options: &Options,
+        anchors: Anchors,
+    ) -> String {
+        let this = Kind::SelfArg.link(url_root, file, symbols, options, anchors);
+        let symbol = format!("`{}`", self.symbol());
+        let operand = match &self.operand {
+            Some(Kind::Tuple(kinds)) => kinds
+                .iter()
+                .map(|k| k.link(url_root, file, symbols, options, anchors))
+                .collect::<Vec<String>>()
+                .join(", "),
+            Some(kind) => kind.link(url_root, file, symbols, options, anchors),
+            None => String::new(),
+        };
+        // render the operator as expression, e.g. `self + Vector`
+        let expression = match self.name.as_str() {
+            "unm" | "bnot" | "len" => format!("{}{}", symbol, this),
+            _ => format!("{} {} {}", this, symbol, operand),
+        };
+        let mut content = hash(&h3(&expression), anchor);
+        if let Some(result) = &self.result {
+            content.push_str(&format!(
+                "\n`->`{}  \n",
+                result.link(url_root, file, symbols, options, anchors)
+            ));
+        }
+        if let Some(desc) = self.desc.as_ref().filter(|d| !d.is_empty()) {
+            content.push_str(&format!("\n{}", description(desc)));
+        }
+        content
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 impl Class {
     fn render(
         &self,
         url_root: &str,
         render_toc: bool,
-        structs: &HashMap<String, Class>,
-        aliases: &HashMap<String, Alias>,
+        library: &Library,
         options: &Options,
+        anchors: Anchors,
     ) -> String {
+        let symbols = &library.symbols;
         let name = if self.name == "global" {
             "Global"
         } else {
@@ -490,12 +1021,59 @@ impl Class {
         };
         let file = self.file.clone().unwrap_or_default();
 
-        let mut content = vec![h1(&hash(name, name))];
+        let anchor = anchors.anchor(&Target::class(&self.name));
+        let mut content = match &self.scope {
+            Scope::Require(module) => vec![h1(&hash(&format!("require(\"{}\")", module), &anchor))],
+            _ => vec![h1(&hash(name, &anchor))],
+        };
 
         if !self.desc.is_empty() {
             content.push(description(&self.desc))
         }
 
+        // link classes whose constructors create instances of this class
+        let constructed_by = self
+            .constructed_by
+            .iter()
+            .filter_map(|name| symbols.resolve_string(name))
+            .map(|kind| kind.link(url_root, &file, symbols, options, anchors))
+            .collect::<Vec<_>>();
+        if !constructed_by.is_empty() {
+            content.push(format!(
+                "Instances are created by {}.",
+                constructed_by.join(", ")
+            ));
+        }
+
+        if self.scope == Scope::Modules {
+            let lua_version = options.lua_version.unwrap_or_default();
+            if let Some(url) = lua_version.manual_url(&self.name) {
+                if options.std_docs.contains(&self.name) {
+                    content.push(format!(
+                        "The Lua standard library `{}` module. See also the [Lua manual]({}).",
+                        self.name, url
+                    ));
+                } else {
+                    content.push(format!(
+                        "Extensions to the Lua standard library `{}` module. See the [Lua manual]({}) for all other `{}` functions.",
+                        self.name, url, self.name
+                    ));
+                }
+            }
+        }
+
+        // link partial definitions of classes to the page of their primary definition
+        if options.order == OutputOrder::ByFile && self.name != "global" {
+            if let Some(id) = symbols.id(&self.name) {
+                if symbols.get(id).file != self.file {
+                    content.push(format!(
+                        "Members of {} which are defined in this file.",
+                        Kind::Class(id).link(url_root, &file, symbols, options, anchors)
+                    ));
+                }
+            }
+        }
+
         if render_toc {
             content.push("\n<!-- toc -->\n".to_string());
         }
@@ -511,13 +1089,35 @@ impl Class {
                     .map(|e| {
                         let name = e.name.clone();
                         let end = Class::get_end(&name).unwrap_or(&name);
-                        format!("{}\n{}", hash(&h3(end), end), description(&e.desc))
+                        let anchor = anchors.anchor(&Target::member(&self.name, end));
+                        format!("{}\n{}", hash(&h3(end), &anchor), description(&e.desc))
                     })
                     .collect::<Vec<String>>()
                     .join("\n"),
                 constants
                     .iter()
-                    .map(|v| v.long(url_root, &file, options))
+                    .map(|v| {
+                        let name = v.name.as_deref().unwrap_or_default();
+                        let anchor = anchors.anchor(&Target::member(&self.name, name));
+                        v.long(&anchor, url_root, &file, symbols, options, anchors)
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ))
+        }
+
+        if !self.constructors.is_empty() {
+            content.push("\n---".to_string());
+            content.push(format!(
+                "{}\n{}\n",
+                h2("Constructors"),
+                self.constructors
+                    .iter()
+                    .map(|f| {
+                        let name = f.name.as_deref().unwrap_or("fun");
+                        let anchor = anchors.anchor(&Target::function(&self.name, name));
+                        f.long(&anchor, url_root, &file, symbols, options, anchors)
+                    })
                     .collect::<Vec<String>>()
                     .join("\n")
             ))
@@ -530,7 +1130,28 @@ impl Class {
                 h2("Properties"),
                 self.fields
                     .iter()
-                    .map(|v| v.long(url_root, &file, options))
+                    .map(|v| {
+                        let name = v.name.as_deref().unwrap_or_default();
+                        let anchor = anchors.anchor(&Target::member(&self.name, name));
+                        v.long(&anchor, url_root, &file, symbols, options, anchors)
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ))
+        }
+
+        if !self.operators.is_empty() {
+            content.push("\n---".to_string());
+            content.push(format!(
+                "{}\n{}\n",
+                h2("Operators"),
+                self.operators
+                    .iter()
+                    .map(|o| {
+                        let name = format!("__{}", o.name);
+                        let anchor = anchors.anchor(&Target::member(&self.name, &name));
+                        o.long(&anchor, url_root, &file, symbols, options, anchors)
+                    })
                     .collect::<Vec<String>>()
                     .join("\n")
             ))
@@ -544,51 +1165,16 @@ impl Class {
                 h2("Functions"),
                 functions
                     .iter()
-                    .map(|f| f.long(url_root, &file, options))
+                    .map(|f| {
+                        let name = f.name.as_deref().unwrap_or("fun");
+                        let anchor = anchors.anchor(&Target::function(&self.name, name));
+                        f.long(&anchor, url_root, &file, symbols, options, anchors)
+                    })
                     .collect::<Vec<String>>()
                     .join("\n")
             ))
         }
 
-        // append used local classes and aliases
-        let (local_class_names, local_alias_names) = match options.order {
-            // when organizing by files, inline used aliases only
-            OutputOrder::ByFile => (HashSet::new(), self.collect_local_aliases(aliases)),
-            // when organizing by class, inline everything the class refers to
-            OutputOrder::ByClass => self.collect_local_types(structs, aliases),
-        };
-
-        // append all used local classes (structs)
-        if self.scope != Scope::Local && !local_class_names.is_empty() {
-            content.push("\n\n\n---".to_string());
-            content.push(h2("Structs"));
-            let mut class_names: Vec<&String> = structs.keys().collect();
-            class_names.sort();
-            for name in class_names {
-                if local_class_names.contains(name) {
-                    let struct_ = structs.get(name).unwrap();
-                    let render_toc = false;
-                    content.push(struct_.render(url_root, render_toc, structs, aliases, options));
-                }
-            }
-        }
-
-        // append all used local aliases
-        if !local_alias_names.is_empty() {
-            content.push("\n\n\n---".to_string());
-            content.push(h2("Aliases"));
-            let mut alias_names: Vec<&String> = aliases.keys().collect();
-            alias_names.sort();
-            for name in alias_names {
-                if local_alias_names.contains(name) {
-                    let file = self.file.clone().unwrap_or_default();
-                    content.push(aliases.get(name).unwrap().render(url_root, &file, options));
-                    content.push(String::new());
-                }
-            }
-        }
-
-        content.push("\n".to_string());
         content.join("  \n")
     }
 }
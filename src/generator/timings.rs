@@ -0,0 +1,43 @@
+use std::{fmt, time::Duration};
+
+// -------------------------------------------------------------------------------------------------
+
+/// Time spent in each phase of the doc generation.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Timings {
+    /// Running lua-language-server and loading its JSON output.
+    pub luals_export: Duration,
+    /// Parsing JSON definitions into classes, aliases and functions.
+    pub parsing: Duration,
+    /// Resolving and filtering types and building the library.
+    pub resolution: Duration,
+    /// Rendering markdown pages.
+    pub rendering: Duration,
+    /// Checking the links of the rendered pages.
+    pub link_check: Duration,
+    /// Writing markdown pages and the TOC.
+    pub writing: Duration,
+}
+
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.luals_export
+            + self.parsing
+            + self.resolution
+            + self.rendering
+            + self.link_check
+            + self.writing
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  LuaLS export: {:>10.3?}", self.luals_export)?;
+        writeln!(f, "  parsing:      {:>10.3?}", self.parsing)?;
+        writeln!(f, "  resolution:   {:>10.3?}", self.resolution)?;
+        writeln!(f, "  rendering:    {:>10.3?}", self.rendering)?;
+        writeln!(f, "  link check:   {:>10.3?}", self.link_check)?;
+        writeln!(f, "  writing:      {:>10.3?}", self.writing)?;
+        write!(f, "  total:        {:>10.3?}", self.total())
+    }
+}
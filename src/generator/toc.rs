@@ -4,7 +4,7 @@ use std::{
     path::Path,
 };
 
-use crate::{error::Error, generator::options::Options, parser::types::Class};
+use crate::{error::Error, generator::options::Options};
 
 // -------------------------------------------------------------------------------------------------
 
@@ -34,21 +34,15 @@ impl TocEntry {
                 .collect::<Vec<_>>()
                 .join("/");
             file_path.push('/');
-        } else if Class::belongs_to_namespace(name, &options.namespace) {
-            if name == options.namespace {
-                // namespace root
-                display_name = name.to_string();
-            } else {
-                // members of namespace
-                display_name = name.replace(&(options.namespace.clone() + "."), "");
-                file_path = options.namespace.clone() + "/";
-                level = "  ".repeat(2);
-            }
+        } else if options.namespaces.iter().any(|namespace| namespace == name) {
+            // namespace root: members of the namespace are nested in sub directories
+            display_name = name.to_string();
         } else {
             // everything else...
             display_name = match name {
                 "builtins" => "Builtin Types".to_string(),
                 "modules" => "Module Extensions".to_string(),
+                "require" => "Modules".to_string(),
                 "structs" => "Helper Types".to_string(),
                 "global" => "Globals".to_string(),
                 _ => name.to_string(),
@@ -12,5 +12,5 @@ mod parser;
 pub use error::Error;
 pub use generator::{
     generate_docs,
-    options::{Options, OutputOrder},
+    options::{LuaVersion, Options, OutputOrder},
 };
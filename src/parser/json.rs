@@ -1,10 +1,13 @@
 use std::{
+    collections::HashMap,
     fmt, fs,
     path::{absolute, Path, PathBuf},
     process::Command,
     str::FromStr,
+    sync::LazyLock,
 };
 
+use regex::Regex;
 use serde::{Deserialize, Deserializer, Serialize};
 use tempdir::TempDir;
 use url::Url;
@@ -16,16 +19,60 @@ use crate::{error::Error, parser::types::LuaKind};
 pub struct JsonDoc {}
 
 impl JsonDoc {
-    /// Generate a list of definitions from a file
-    pub fn get(path: &Path) -> Result<Vec<Definition>, Error> {
-        let defs = Self::export(path)?;
-        Ok(Self::strip(path, defs))
+    /// Generate a list of definitions from a file. Definitions from the LuaLS standard
+    /// library meta files are kept for the given std module names only.
+    pub fn get(path: &Path, std_modules: &[String]) -> Result<Vec<Definition>, Error> {
+        let ls_path = Self::luals_path()?;
+        let defs = Self::export(&ls_path, path)?;
+        let meta_path = if std_modules.is_empty() {
+            None
+        } else {
+            Self::luals_meta_path(&ls_path)
+        };
+        Ok(Self::prepare(path, meta_path.as_deref(), std_modules, defs))
     }
 
-    /// Export and parse the JSON docs from lua-language-server
-    fn export(path: &Path) -> Result<Vec<Definition>, Error> {
-        let tmp_dir = TempDir::new("docs")?;
-        let tmp_path = tmp_dir.path();
+    /// Prepare definitions, as exported by LuaLS for the library at the given path: strips
+    /// definitions from other files than the library and the std meta files of the given
+    /// modules and adds annotations which LuaLS does not export.
+    pub(crate) fn prepare(
+        path: &Path,
+        meta_path: Option<&Path>,
+        std_modules: &[String],
+        defs: Vec<Definition>,
+    ) -> Vec<Definition> {
+        let mut defs = Self::strip(path, meta_path, std_modules, defs);
+        Self::add_class_annotations(path, &mut defs);
+        defs
+    }
+
+    /// Returns the `Lua.runtime.version` which is configured in the LuaLS config of the given
+    /// library path, either in a `.luarc.json` or in a `config.json` addon config file.
+    pub fn runtime_version(path: &Path) -> Option<String> {
+        let dir = if path.is_dir() { path } else { path.parent()? };
+        let read_config = |file_name: &str| {
+            fs::read_to_string(dir.join(file_name))
+                .ok()
+                .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        };
+        let luarc_version = read_config(".luarc.json").and_then(|config| {
+            config
+                .get("runtime.version")
+                .or_else(|| config.get("Lua.runtime.version"))
+                .or_else(|| config.pointer("/runtime/version"))
+                .and_then(|version| version.as_str().map(|v| v.to_string()))
+        });
+        luarc_version.or_else(|| {
+            read_config("config.json").and_then(|config| {
+                config
+                    .pointer("/settings/Lua.runtime.version")
+                    .and_then(|version| version.as_str().map(|v| v.to_string()))
+            })
+        })
+    }
+
+    /// Locate the lua-language-server binary and download it, if necessary
+    fn luals_path() -> Result<PathBuf, Error> {
         let ls_filename = if cfg!(windows) {
             "lua-language-server.exe"
         } else {
@@ -44,6 +91,13 @@ impl JsonDoc {
                     .to_string_lossy()
             )));
         }
+        Ok(ls_path)
+    }
+
+    /// Export and parse the JSON docs from lua-language-server
+    fn export(ls_path: &Path, path: &Path) -> Result<Vec<Definition>, Error> {
+        let tmp_dir = TempDir::new("docs")?;
+        let tmp_path = tmp_dir.path();
         let output = Command::new(ls_path)
             .arg("--doc")
             .arg(path)
@@ -162,6 +216,8 @@ impl JsonDoc {
         }
     }
 
+    /// Returns true when the given file url points to a file within the given canonicalized
+    /// base path.
     fn file_url_matches(file_url: &str, base_path: &Path) -> bool {
         assert!(
             Url::from_str(file_url).is_ok(),
@@ -173,22 +229,216 @@ impl JsonDoc {
             .unwrap_or_default()
             .canonicalize()
             .unwrap_or_default();
-        let base_path = base_path.canonicalize().unwrap_or_default();
         file_path.starts_with(base_path)
     }
 
-    /// Exclude standard lua
-    fn strip(path: &Path, defs: Vec<Definition>) -> Vec<Definition> {
+    /// Returns true when the given file url points to a LuaLS meta file of one of the
+    /// given standard library modules, e.g. `meta/Lua 5.1 en-us utf8/string.lua`.
+    fn is_std_meta_file(file_url: &str, meta_path: &Path, std_modules: &[String]) -> bool {
+        if !Self::file_url_matches(file_url, meta_path) {
+            return false;
+        }
+        let file_stem = Url::from_str(file_url)
+            .ok()
+            .and_then(|url| url.to_file_path().ok())
+            .and_then(|path| path.file_stem().map(|s| s.to_string_lossy().to_string()))
+            .unwrap_or_default();
+        // submodules such as `table.new` are part of their parent module
+        std_modules.iter().any(|module| {
+            file_stem == *module || file_stem.starts_with(&(module.to_string() + "."))
+        })
+    }
+
+    /// Exclude standard lua, except the module functions of the given std modules, which are
+    /// defined in the given canonicalized LuaLS meta path
+    fn strip(
+        path: &Path,
+        meta_path: Option<&Path>,
+        std_modules: &[String],
+        defs: Vec<Definition>,
+    ) -> Vec<Definition> {
+        let base_path = path.canonicalize().unwrap_or_default();
         defs.into_iter()
             .map(|d| {
                 // remove standard define from the list of defines (for type())
                 let mut def = d.clone();
-                def.defines
-                    .retain(|define| Self::file_url_matches(&define.file, path));
+                def.defines.retain(|define| {
+                    Self::file_url_matches(&define.file, &base_path)
+                        || (matches!(
+                            define.lua_type,
+                            Type::SetField | Type::SetGlobal | Type::SetMethod
+                        ) && meta_path.is_some_and(|meta_path| {
+                            Self::is_std_meta_file(&define.file, meta_path, std_modules)
+                        }))
+                });
                 def
             })
             .collect()
     }
+
+    /// LuaLS does not export `---@operator` and `---@overload` annotations of classes, so parse
+    /// them from the annotation blocks of the class defines in the source files. Also detects
+    /// classes which are returned by module files, see [`Self::module_name`].
+    fn add_class_annotations(path: &Path, defs: &mut [Definition]) {
+        static OPERATOR_RE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(
+                r"^---\s*@operator\s+(\w+)\s*(?:\((.*?)\))?\s*(?::\s*([^#]+?))?\s*(?:#\s*(.*))?$",
+            )
+            .unwrap()
+        });
+        static OVERLOAD_RE: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"^---\s*@overload\s+(fun\b[^#]*?)\s*(?:#\s*(.*))?$").unwrap()
+        });
+        let mut sources = HashMap::<String, Vec<String>>::new();
+        for def in defs.iter_mut() {
+            for define in &def.defines {
+                if define.lua_type != Type::Doc(Doc::Class) {
+                    continue;
+                }
+                let lines = sources.entry(define.file.clone()).or_insert_with(|| {
+                    Url::from_str(&define.file)
+                        .ok()
+                        .and_then(|url| url.to_file_path().ok())
+                        .and_then(|path| fs::read_to_string(path).ok())
+                        .map(|content| content.lines().map(|l| l.to_string()).collect())
+                        .unwrap_or_default()
+                });
+                // LuaLS positions are encoded as `line * 10000 + column`
+                let class_line = (define.start / 10000) as usize;
+                let mut desc = vec![];
+                let mut declaration = None;
+                for (index, line) in lines.iter().enumerate().skip(class_line + 1) {
+                    let line = line.trim();
+                    if !line.starts_with("---") {
+                        declaration = Some(line);
+                        break; // end of the annotation block
+                    }
+                    if let Some(captures) = OPERATOR_RE.captures(line) {
+                        let capture = |i: usize| {
+                            captures
+                                .get(i)
+                                .map(|c| c.as_str().trim().to_string())
+                                .filter(|c| !c.is_empty())
+                        };
+                        let inline_desc = capture(4);
+                        let desc = std::mem::take(&mut desc);
+                        def.operators.push(OperatorDef {
+                            name: capture(1).unwrap_or_default(),
+                            operand: capture(2),
+                            result: capture(3),
+                            desc: inline_desc.or(Some(desc.join("\n")).filter(|d| !d.is_empty())),
+                            start: (index as u32) * 10000,
+                        });
+                    } else if let Some(captures) = OVERLOAD_RE.captures(line) {
+                        let inline_desc = captures
+                            .get(2)
+                            .map(|c| c.as_str().trim().to_string())
+                            .filter(|c| !c.is_empty());
+                        let desc = std::mem::take(&mut desc);
+                        def.overloads.push(OverloadDef {
+                            view: captures[1].to_string(),
+                            desc: inline_desc.or(Some(desc.join("\n")).filter(|d| !d.is_empty())),
+                            start: (index as u32) * 10000,
+                        });
+                    } else if line.trim_start_matches('-').trim_start().starts_with('@') {
+                        desc.clear(); // descriptions of other annotations
+                    } else {
+                        desc.push(line.trim_start_matches('-').trim().to_string());
+                    }
+                }
+                if let Some(declaration) = declaration {
+                    def.module = Self::module_name(path, &define.file, lines, declaration);
+                }
+            }
+        }
+    }
+
+    /// Returns the `require` name of the module file at the given file url, when the given
+    /// declaration is the local table the module file returns, e.g. `local M = {}` and
+    /// `return M`. The name is either set explicitly via a `---@meta name` header or gets
+    /// derived from the file path relative to the library path, as `require` does.
+    /// `---@module name` annotations don't name the file: they mark locals as the result of
+    /// `require("name")`.
+    ///
+    /// NB: LuaLS only exports local tables which are annotated as `---@class`, so modules
+    /// which return plain, unannotated tables can't be detected and documented.
+    fn module_name(
+        path: &Path,
+        file_url: &str,
+        lines: &[String],
+        declaration: &str,
+    ) -> Option<String> {
+        static LOCAL_RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"^local\s+(\w+)\s*=").unwrap());
+        static META_RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r#"^---\s*@meta\s+["']?([\w.\-]+)["']?\s*$"#).unwrap());
+        let table = &LOCAL_RE.captures(declaration)?[1];
+        let last_statement = lines
+            .iter()
+            .map(|line| line.trim())
+            .rfind(|line| !line.is_empty() && !line.starts_with("--"))?;
+        if last_statement.trim_end_matches(';').trim_end() != format!("return {}", table) {
+            return None;
+        }
+        // the `@meta` header precedes all code in the file
+        if let Some(name) = lines
+            .iter()
+            .map(|line| line.trim())
+            .take_while(|line| line.is_empty() || line.starts_with("--"))
+            .find_map(|line| META_RE.captures(line).map(|c| c[1].to_string()))
+            .filter(|name| name != "_")
+        {
+            return Some(name);
+        }
+        let file_path = Url::from_str(file_url).ok()?.to_file_path().ok()?;
+        let root = Self::library_root(path)?;
+        let relative = file_path.canonicalize().ok()?;
+        let relative = relative.strip_prefix(root).ok()?.with_extension("");
+        let mut parts = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        // `require("foo")` also loads `foo/init.lua`
+        if parts.len() > 1 && parts.last().is_some_and(|part| part == "init") {
+            parts.pop();
+        }
+        Some(parts.join("."))
+    }
+
+    /// Returns the decoded path of the given file url, relative to the given library path.
+    /// Files outside of the library path are returned as absolute paths.
+    pub fn relative_path(path: &Path, file_url: &str) -> Option<PathBuf> {
+        let file_path = Url::from_str(file_url).ok()?.to_file_path().ok()?;
+        let file_path = file_path.canonicalize().unwrap_or(file_path);
+        match Self::library_root(path) {
+            Some(root) if file_path.starts_with(&root) => {
+                file_path.strip_prefix(root).ok().map(Path::to_path_buf)
+            }
+            _ => Some(file_path),
+        }
+    }
+
+    /// Canonicalized root directory of the given library directory or file.
+    fn library_root(path: &Path) -> Option<PathBuf> {
+        let path = path.canonicalize().ok()?;
+        if path.is_dir() {
+            Some(path)
+        } else {
+            path.parent().map(Path::to_path_buf)
+        }
+    }
+
+    /// Canonicalized path of the meta definitions directory of the given LuaLS binary, which
+    /// is located next to the binary's `bin` directory.
+    fn luals_meta_path(ls_path: &Path) -> Option<PathBuf> {
+        let root = ls_path
+            .canonicalize()
+            .ok()?
+            .parent()?
+            .parent()?
+            .to_path_buf();
+        root.join("meta").canonicalize().ok()
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -359,6 +609,18 @@ pub struct Definition {
     pub defines: Vec<Define>,
     #[serde(default)]
     pub fields: Vec<Field>,
+    /// Not part of the LuaLS export: added from the class sources, see
+    /// [`JsonDoc::add_class_annotations`]
+    #[serde(default)]
+    pub operators: Vec<OperatorDef>,
+    /// Not part of the LuaLS export: added from the class sources, see
+    /// [`JsonDoc::add_class_annotations`]
+    #[serde(default)]
+    pub overloads: Vec<OverloadDef>,
+    /// Not part of the LuaLS export: the `require` name of classes which are returned by
+    /// module files, see [`JsonDoc::module_name`]
+    #[serde(default)]
+    pub module: Option<String>,
 }
 
 impl fmt::Display for Definition {
@@ -404,6 +666,28 @@ impl fmt::Display for Definition {
 
 // -------------------------------------------------------------------------------------------------
 
+/// A `---@operator name(operand): result` annotation of a class
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct OperatorDef {
+    pub name: String,
+    pub operand: Option<String>,
+    pub result: Option<String>,
+    pub desc: Option<String>,
+    pub start: u32,
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// A `---@overload fun(...)` annotation of a class, which makes the class table callable
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct OverloadDef {
+    pub view: String,
+    pub desc: Option<String>,
+    pub start: u32,
+}
+
+// -------------------------------------------------------------------------------------------------
+
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Define {
     #[serde(rename = "type")]
@@ -522,3 +806,78 @@ pub struct ReturnDef {
     pub name: Option<String>,
     pub view: String,
 }
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn relative_path() {
+        let path = PathBuf::from("./test/definitions");
+        let file = path.join("library/acme/some class.lua");
+        let file_url = Url::from_file_path(absolute(&file).unwrap()).unwrap();
+        assert!(file_url.as_str().ends_with("/some%20class.lua"));
+        assert_eq!(
+            JsonDoc::relative_path(&path, file_url.as_str()),
+            Some(PathBuf::from("library/acme/some class.lua"))
+        );
+    }
+
+    #[test]
+    fn module_names() {
+        let tmp_dir = TempDir::new("modules").unwrap();
+        let library = tmp_dir.path().join("library");
+        let module_name = |file: &str, content: &str| {
+            let path = library.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+            let file_url = Url::from_file_path(path.canonicalize().unwrap()).unwrap();
+            let lines = content.lines().map(String::from).collect::<Vec<_>>();
+            let declaration = lines.iter().find(|l| l.starts_with("local M")).unwrap();
+            JsonDoc::module_name(&library, file_url.as_str(), &lines, declaration)
+        };
+        let module = "---@class Foo\nlocal M = {}\nfunction M.run() end\nreturn M\n";
+        // names are derived from the path relative to the library
+        assert_eq!(module_name("foo.lua", module), Some("foo".to_string()));
+        assert_eq!(
+            module_name("foo/bar.lua", module),
+            Some("foo.bar".to_string())
+        );
+        // `init.lua` files are loaded via their directory name
+        assert_eq!(module_name("foo/init.lua", module), Some("foo".to_string()));
+        assert_eq!(module_name("init.lua", module), Some("init".to_string()));
+        // explicit module names
+        let named = format!("---@meta acme.bar\n{}", module);
+        assert_eq!(
+            module_name("foo/baz.lua", &named),
+            Some("acme.bar".to_string())
+        );
+        let unnamed = format!("---@meta _\n{}", module);
+        assert_eq!(
+            module_name("foo/baz.lua", &unnamed),
+            Some("foo.baz".to_string())
+        );
+        // `@meta` annotations after the header and `@module` annotations of imports don't
+        // name the module
+        let imports = format!(
+            "---@module \"acme.foo\"\nlocal foo = require(\"acme.foo\")\n---@meta acme.bar\n{}",
+            module
+        );
+        assert_eq!(
+            module_name("foo/baz.lua", &imports),
+            Some("foo.baz".to_string())
+        );
+        // only the returned local table is a module
+        let other = "---@class Foo\nlocal M = {}\nlocal N = {}\nreturn N\n";
+        assert_eq!(module_name("other.lua", other), None);
+    }
+
+    #[test]
+    fn runtime_version() {
+        let path = PathBuf::from("./test/definitions");
+        assert_eq!(JsonDoc::runtime_version(&path), Some("Lua 5.1".to_string()));
+        assert_eq!(JsonDoc::runtime_version(&path.join("library")), None);
+    }
+}
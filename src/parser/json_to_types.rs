@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 
 use crate::parser::{
-    json::{ArgDef, ArgType, Definition, Doc, Extend, Field, ReturnDef, Type},
+    json::{
+        ArgDef, ArgType, Definition, Doc, Extend, Field, OperatorDef, OverloadDef, ReturnDef, Type,
+        VisibleType,
+    },
     lua_parser::LuaParser,
     types::*,
 };
@@ -9,15 +12,40 @@ use crate::parser::{
 // -------------------------------------------------------------------------------------------------
 
 impl Def {
-    pub fn from_definition(definition: &Definition, namespace: &str) -> Option<Self> {
+    /// Convert a LuaLS definition. Types which fail to parse are added to the given diagnostics.
+    pub fn from_definition(
+        definition: &Definition,
+        namespaces: &[String],
+        std_modules: &[String],
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<Self> {
         if let Some(first) = definition.defines.first() {
+            let mut context = ParseContext {
+                file: Some(first.file.clone().into()),
+                line_number: Some(first.start),
+                symbol: definition.name.clone(),
+                diagnostics,
+            };
             match first.lua_type {
-                Type::Doc(Doc::Class) => Some(Self::Class(Class::from_definition(
-                    definition,
-                    namespace,
-                    &first.file,
-                    first.start,
-                ))),
+                Type::Doc(Doc::Class) => {
+                    // classes can be declared in multiple files: locate them at their first
+                    // declaration in file order, independent of the order of LuaLS's defines
+                    if let Some(define) = definition
+                        .defines
+                        .iter()
+                        .filter(|define| define.lua_type == Type::Doc(Doc::Class))
+                        .min_by(|a, b| (&a.file, a.start).cmp(&(&b.file, b.start)))
+                    {
+                        context.file = Some(define.file.clone().into());
+                        context.line_number = Some(define.start);
+                    }
+                    Some(Self::Class(Class::from_definition(
+                        definition,
+                        namespaces,
+                        std_modules,
+                        &mut context,
+                    )))
+                }
 
                 // TODO parse fields in enum either from desc or from Type::TableField
                 Type::Doc(Doc::Enum) => Some(Self::Enum(Enum {
@@ -35,7 +63,7 @@ impl Def {
                     kind: first
                         .clone()
                         .extends
-                        .map(|e| Kind::from_string(&e.view))
+                        .map(|e| context.kind(&e.view))
                         .unwrap_or(Kind::Unresolved(definition.name.clone())),
                 })),
 
@@ -47,9 +75,10 @@ impl Def {
                                 first.file.clone().into(),
                                 definition.name.clone(),
                                 definition.rawdesc.clone().unwrap_or_default(),
+                                &mut context,
                             )
                             .map(Self::Function),
-                            _ => None,
+                            _ => Var::from_global(definition, extend, &mut context).map(Self::Var),
                         }
                     } else {
                         None
@@ -65,44 +94,83 @@ impl Def {
 
 // -------------------------------------------------------------------------------------------------
 
-impl Kind {
-    fn from_string(s: &str) -> Self {
-        LuaParser::type_def(s)
-    }
+/// Location and symbol path of the definition which is currently converted. Types which
+/// fail to parse get reported as diagnostics with this location.
+struct ParseContext<'a> {
+    file: Option<PathBuf>,
+    line_number: Option<u32>,
+    symbol: String,
+    diagnostics: &'a mut Vec<Diagnostic>,
 }
 
-impl From<&String> for Kind {
-    fn from(s: &String) -> Self {
-        LuaParser::type_def(s.as_str())
-    }
-}
-impl From<Type> for Kind {
-    fn from(t: Type) -> Self {
-        match t {
-            Type::Lua(lk) => Self::Lua(lk),
-            _ => Self::Unresolved(format!("{:?}", t)),
+impl ParseContext<'_> {
+    /// create a context for a child symbol, e.g. a field or function param
+    fn child(
+        &mut self,
+        symbol: String,
+        file: Option<PathBuf>,
+        line_number: Option<u32>,
+    ) -> ParseContext<'_> {
+        ParseContext {
+            file: file.or(self.file.clone()),
+            line_number: line_number.or(self.line_number),
+            symbol,
+            diagnostics: self.diagnostics,
         }
     }
-}
-impl From<Extend> for Kind {
-    fn from(e: Extend) -> Self {
-        // Self::Unresolved("missing type".to_string())
+
+    /// parse a type string, falling back to an unresolved kind on errors
+    fn kind(&mut self, s: &str) -> Kind {
+        LuaParser::type_def(s).unwrap_or_else(|message| {
+            self.diagnostics.push(Diagnostic {
+                file: self.file.clone(),
+                line_number: self.line_number,
+                symbol: self.symbol.clone(),
+                message,
+            });
+            Kind::Unresolved(s.to_string())
+        })
+    }
+
+    fn kind_from_extend(&mut self, e: Extend) -> Kind {
         if let Some(types) = e.types {
             if types.len() > 1 {
-                Self::Enum(types.iter().map(|t| Self::from_string(&t.view)).collect())
+                Kind::Enum(types.iter().map(|t| self.kind(&t.view)).collect())
             } else if let Some(first) = types.first() {
                 if first.view.clone() + "?" == e.view {
-                    Self::from_string(&e.view)
+                    self.kind(&e.view)
                 } else {
-                    Self::from_string(&first.view)
+                    self.kind(&first.view)
                 }
             } else {
-                Self::from_string(&e.view)
+                self.kind(&e.view)
             }
         } else if e.lua_type == Type::GetLocal || e.lua_type == Type::GetField {
-            Self::from_string(&e.view)
+            self.kind(&e.view)
         } else {
-            Self::from(e.lua_type)
+            Kind::from(e.lua_type)
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl From<Type> for Kind {
+    fn from(t: Type) -> Self {
+        match t {
+            Type::Lua(lk) => Self::Lua(lk),
+            _ => Self::Unresolved(format!("{:?}", t)),
+        }
+    }
+}
+
+impl From<VisibleType> for Visibility {
+    fn from(v: VisibleType) -> Self {
+        match v {
+            VisibleType::Public => Self::Public,
+            VisibleType::Protected => Self::Protected,
+            VisibleType::Package => Self::Package,
+            VisibleType::Private => Self::Private,
         }
     }
 }
@@ -110,13 +178,19 @@ impl From<Extend> for Kind {
 // -------------------------------------------------------------------------------------------------
 
 impl Var {
-    fn from_field(field: Field) -> Option<Self> {
+    fn from_field(field: Field, context: &mut ParseContext) -> Option<Self> {
+        let mut context = context.child(
+            format!("{}.{}", context.symbol, field.name),
+            Some(field.file.clone().into()),
+            Some(field.start),
+        );
         Some(Self {
+            visibility: field.visible.into(),
             file: Some(field.file.clone().into()),
             line_number: Some(field.start),
             kind: field
                 .extends
-                .map(Kind::from)
+                .map(|e| context.kind_from_extend(e))
                 .unwrap_or(Kind::Unresolved(format!(
                     "there are no extends on field {}",
                     field.name.clone()
@@ -125,25 +199,61 @@ impl Var {
             desc: field.rawdesc,
         })
     }
-    fn from_argdef(ad: &ArgDef) -> Option<Self> {
+    /// convert a global variable or a field of a namespace table. only documented variables,
+    /// which have a description or an explicit `---@type`, are converted.
+    fn from_global(
+        definition: &Definition,
+        extend: Extend,
+        context: &mut ParseContext,
+    ) -> Option<Self> {
+        let has_desc = definition
+            .rawdesc
+            .as_ref()
+            .is_some_and(|desc| !desc.trim().is_empty());
+        let has_type = extend.lua_type == Type::Doc(Doc::Type);
+        if !has_desc && !has_type {
+            return None;
+        }
+        Some(Self {
+            visibility: Visibility::Public,
+            file: context.file.clone(),
+            line_number: context.line_number,
+            kind: context.kind_from_extend(extend),
+            name: Some(definition.name.clone()),
+            desc: definition.rawdesc.clone(),
+        })
+    }
+    fn from_argdef(ad: &ArgDef, context: &mut ParseContext) -> Option<Self> {
+        let symbol = format!(
+            "{} param `{}`",
+            context.symbol,
+            ad.name.clone().unwrap_or("...".to_string())
+        );
+        let mut context = context.child(symbol, None, None);
         Some(Self {
             file: None,
             line_number: None,
             kind: match ad.lua_type {
                 ArgType::SelfArg => Kind::SelfArg,
-                ArgType::Local => Kind::from_string(&ad.view),
-                ArgType::Variadic => Kind::Variadic(Box::new(Kind::from_string(&ad.view))),
+                ArgType::Local => context.kind(&ad.view),
+                ArgType::Variadic => Kind::Variadic(Box::new(context.kind(&ad.view))),
             },
             name: ad.name.clone(),
             desc: None, // desc: ad.desc.unwrap_or_default(),
+            visibility: Visibility::Public,
         })
     }
 
-    fn from_return(rd: &ReturnDef) -> Option<Self> {
+    fn from_return(rd: &ReturnDef, index: usize, context: &mut ParseContext) -> Option<Self> {
+        let symbol = match &rd.name {
+            Some(name) => format!("{} return `{}`", context.symbol, name),
+            None => format!("{} return #{}", context.symbol, index + 1),
+        };
+        let mut context = context.child(symbol, None, None);
         Some(Self {
             file: None,
             line_number: None,
-            kind: Kind::from_string(&rd.view),
+            kind: context.kind(&rd.view),
             // kind: match rd.lua_type {
             //     ArgType::SelfArg => ArgKind::SelfKind,
             //     ArgType::Local => ArgKind::from_string(rd.view.as_str()),
@@ -151,6 +261,7 @@ impl Var {
             // },
             name: rd.name.clone(),
             desc: None, // desc: rd.desc.unwrap_or_default(),
+            visibility: Visibility::Public,
         })
     }
 }
@@ -158,18 +269,30 @@ impl Var {
 // -------------------------------------------------------------------------------------------------
 
 impl Function {
-    fn from_extend(extend: Extend, file: PathBuf, name: String, desc: String) -> Option<Self> {
+    fn from_extend(
+        extend: Extend,
+        file: PathBuf,
+        name: String,
+        desc: String,
+        context: &mut ParseContext,
+    ) -> Option<Self> {
         match extend.lua_type {
             Type::Lua(LuaKind::Function) => {
+                let mut context = context.child(
+                    context.symbol.clone(),
+                    Some(file.clone()),
+                    Some(extend.start),
+                );
                 let params = extend
                     .args
                     .iter()
-                    .filter_map(Var::from_argdef)
+                    .filter_map(|ad| Var::from_argdef(ad, &mut context))
                     .collect::<Vec<_>>();
                 let returns = extend
                     .returns
                     .iter()
-                    .filter_map(Var::from_return)
+                    .enumerate()
+                    .filter_map(|(index, rd)| Var::from_return(rd, index, &mut context))
                     .collect::<Vec<Var>>();
                 Some(Self {
                     file: Some(file),
@@ -178,19 +301,53 @@ impl Function {
                     params,
                     returns,
                     desc: Some(desc.to_string()).filter(|s| !s.is_empty()),
+                    visibility: Visibility::Public,
                 })
             }
             _ => None,
         }
     }
-    fn from_field(field: Field) -> Option<Self> {
+    fn from_overload(
+        overload: &OverloadDef,
+        class_name: &str,
+        context: &mut ParseContext,
+    ) -> Option<Self> {
+        let mut context = context.child(
+            format!("{} overload", context.symbol),
+            None,
+            Some(overload.start),
+        );
+        match context.kind(&overload.view) {
+            Kind::Function(function) => Some(Self {
+                file: context.file.clone(),
+                line_number: context.line_number,
+                name: Some(class_name.to_string()),
+                desc: overload.desc.clone(),
+                ..function
+            }),
+            _ => None,
+        }
+    }
+    fn from_field(field: Field, context: &mut ParseContext) -> Option<Self> {
         if let Some(extend) = field.extends {
+            let separator = if field.lua_type == Type::SetMethod {
+                ":"
+            } else {
+                "."
+            };
+            let symbol = format!("{}{}{}", context.symbol, separator, field.name);
+            let mut context = context.child(symbol, None, None);
             Self::from_extend(
                 extend,
                 field.file.into(),
                 field.name.clone(),
                 field.rawdesc.unwrap_or_default(),
+                &mut context,
             )
+            .map(|function| Self {
+                visibility: field.visible.into(),
+                ..function
+            })
         } else {
             None
         }
@@ -199,35 +356,207 @@ impl Function {
 
 // -------------------------------------------------------------------------------------------------
 
+impl Operator {
+    fn from_definition(operator: &OperatorDef, context: &mut ParseContext) -> Self {
+        let mut context = context.child(
+            format!("{} operator `{}`", context.symbol, operator.name),
+            None,
+            Some(operator.start),
+        );
+        Self {
+            file: context.file.clone(),
+            line_number: context.line_number,
+            name: operator.name.clone(),
+            operand: operator
+                .operand
+                .as_ref()
+                .map(|operand| context.kind(operand)),
+            result: operator.result.as_ref().map(|result| context.kind(result)),
+            desc: operator.desc.clone(),
+        }
+    }
+
+    /// convert a `call` operator to a constructor function with unnamed params
+    fn into_constructor(self, class_name: &str) -> Function {
+        let var = |kind: Kind| Var {
+            file: None,
+            line_number: None,
+            name: None,
+            kind,
+            desc: None,
+            visibility: Visibility::Public,
+        };
+        let params = match self.operand {
+            Some(Kind::Tuple(kinds)) => kinds.into_iter().map(var).collect(),
+            Some(kind) => vec![var(kind)],
+            None => vec![],
+        };
+        Function {
+            file: self.file,
+            line_number: self.line_number,
+            name: Some(class_name.to_string()),
+            params,
+            returns: self.result.into_iter().map(var).collect(),
+            desc: self.desc,
+            visibility: Visibility::Public,
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 impl Class {
     fn from_definition(
         definition: &Definition,
-        namespace: &str,
-        file: &str,
-        line_number: u32,
+        namespaces: &[String],
+        std_modules: &[String],
+        context: &mut ParseContext,
     ) -> Self {
+        // call operators and overloads make the class table callable: treat them as constructors
+        let (calls, operators): (Vec<_>, Vec<_>) = definition
+            .operators
+            .iter()
+            .map(|operator| Operator::from_definition(operator, context))
+            .partition(|operator| operator.name == "call");
+        let constructors = calls
+            .into_iter()
+            .map(|operator| operator.into_constructor(&definition.name))
+            .chain(definition.overloads.iter().filter_map(|overload| {
+                Function::from_overload(overload, &definition.name, context)
+            }))
+            .collect();
         Self {
-            file: Some(file.into()),
-            line_number: Some(line_number),
-            scope: Scope::from_name(&definition.name, namespace),
+            file: context.file.clone(),
+            line_number: context.line_number,
+            scope: match &definition.module {
+                Some(module) => Scope::Require(module.clone()),
+                None => Scope::from_name(&definition.name, namespaces, std_modules),
+            },
             name: definition.name.clone(),
             fields: definition
                 .fields
                 .clone()
                 .into_iter()
                 .filter(Field::is_field)
-                .filter_map(Var::from_field)
+                .filter_map(|field| Var::from_field(field, context))
                 .collect(),
             functions: definition
                 .fields
                 .clone()
                 .into_iter()
                 .filter(Field::is_function)
-                .filter_map(Function::from_field)
+                .filter_map(|field| Function::from_field(field, context))
                 .collect(),
             enums: vec![], // enums will get added in Library
             constants: vec![],
+            operators,
+            constructors,
+            constructed_by: vec![], // will get collected in Library
             desc: definition.rawdesc.clone().unwrap_or_default(),
         }
     }
 }
+
+// -------------------------------------------------------------------------------------------------
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // a LuaLS definition of a global assignment with the given description and value extends
+    fn global(name: &str, rawdesc: Option<&str>, extends: &str) -> Definition {
+        let json = format!(
+            r#"{{
+                "type": "variable",
+                "name": "{}",
+                "desc": null,
+                "rawdesc": {},
+                "defines": [{{
+                    "type": "setfield",
+                    "file": "file:///library/acme.lua",
+                    "start": 100000,
+                    "finish": 100040,
+                    "extends": {}
+                }}]
+            }}"#,
+            name,
+            serde_json::to_string(&rawdesc).unwrap(),
+            extends
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn convert(definition: &Definition) -> Option<Def> {
+        Def::from_definition(definition, &["acme".to_string()], &[], &mut vec![])
+    }
+
+    #[test]
+    fn documented_globals() {
+        let table = r#"{
+            "type": "getfield", "start": 100020, "finish": 100040, "view": "acme.SomeClass"
+        }"#;
+        let number = r#"{
+            "type": "number", "start": 100020, "finish": 100023, "view": "number"
+        }"#;
+        let doc_type = r#"{
+            "type": "doc.type", "start": 90010, "finish": 90016, "view": "number",
+            "types": [{ "type": "doc.type.name", "start": 90010, "finish": 90016, "view": "number" }]
+        }"#;
+        // undocumented assignments are skipped
+        assert!(convert(&global("acme.SomeClass.__index", None, table)).is_none());
+        assert!(convert(&global("acme.VALUE", Some(" "), number)).is_none());
+        // globals with a description or an explicit type are documented
+        assert!(matches!(
+            convert(&global("acme.VALUE", Some("Some value"), number)),
+            Some(Def::Var(Var { desc: Some(desc), .. })) if desc == "Some value"
+        ));
+        assert!(matches!(
+            convert(&global("acme.VALUE", None, doc_type)),
+            Some(Def::Var(Var {
+                kind: Kind::Lua(LuaKind::Number),
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn class_location() {
+        let define = |file: &str, start: u32| {
+            format!(
+                r#"{{ "type": "doc.class", "file": "file:///library/{}", "start": {}, "finish": {} }}"#,
+                file,
+                start,
+                start + 10
+            )
+        };
+        let json = format!(
+            r#"{{
+                "type": "type", "name": "acme.SomeClass", "desc": null, "rawdesc": null,
+                "defines": [{}, {}, {}]
+            }}"#,
+            define("b.lua", 10000),
+            define("a.lua", 50000),
+            define("a.lua", 30000)
+        );
+        let definition = serde_json::from_str::<Definition>(&json).unwrap();
+        // classes which are declared in multiple files are located at their first declaration
+        let Some(Def::Class(class)) = convert(&definition) else {
+            panic!("expected a class");
+        };
+        assert_eq!(class.file, Some("file:///library/a.lua".into()));
+        assert_eq!(class.line_number, Some(30000));
+        // merged classes too, independent of the merge order
+        let other = Class {
+            file: Some("file:///library/0.lua".into()),
+            line_number: Some(20000),
+            ..Class::new("acme.SomeClass", Scope::Global)
+        };
+        let mut merged = other.clone();
+        merged.merge(class.clone());
+        assert_eq!(merged.file, other.file);
+        let mut merged = class;
+        merged.merge(other.clone());
+        assert_eq!(merged.file, other.file);
+        assert_eq!(merged.line_number, Some(20000));
+    }
+}
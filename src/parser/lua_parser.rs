@@ -1,9 +1,7 @@
-use std::collections::HashMap;
-
-use pest::{iterators::Pair, Parser};
+use pest::{error::LineColLocation, iterators::Pair, Parser};
 use pest_derive::Parser;
 
-use crate::parser::types::{Function, Kind, LuaKind, Var};
+use crate::parser::types::{Function, Kind, LuaKind, ObjectKey, Var, Visibility};
 
 // -------------------------------------------------------------------------------------------------
 
@@ -12,106 +10,145 @@ use crate::parser::types::{Function, Kind, LuaKind, Var};
 pub struct LuaParser {}
 impl LuaParser {
     /// parse a string into a type definition of Kind
-    pub fn type_def(input: &str) -> Kind {
-        match Self::parse(Rule::type_def, input) {
-            Ok(mut pairs) => {
-                let next = pairs.next().unwrap();
-                let pair = next.into_inner().next().unwrap();
-                Self::kind(pair)
-            }
-            Err(err) => {
-                // warn about parse errors
-                println!("\x1b[33m{}\x1b[0m", err);
-                Kind::Unresolved(input.to_string())
-            }
-        }
+    pub fn type_def(input: &str) -> Result<Kind, String> {
+        let parse = || {
+            let mut pairs = Self::parse(Rule::type_def, input).map_err(|err| {
+                let column = match err.line_col {
+                    LineColLocation::Pos((_, column)) => column,
+                    LineColLocation::Span((_, column), _) => column,
+                };
+                format!("{} at column {}", err.variant.message(), column)
+            })?;
+            let type_def = Self::next(&mut pairs, "type definition")?;
+            Self::kind(Self::next(&mut type_def.into_inner(), "type definition")?)
+        };
+        parse().map_err(|err| format!("failed to parse type `{}`: {}", input, err))
     }
 
     fn as_string(pair: &Pair<Rule>) -> String {
         pair.as_span().as_str().to_string()
     }
 
-    fn named_var(pair: Pair<Rule>) -> Var {
+    // fetch the next expected pair or return an error
+    fn next<'a>(
+        pairs: &mut impl Iterator<Item = Pair<'a, Rule>>,
+        what: &str,
+    ) -> Result<Pair<'a, Rule>, String> {
+        pairs.next().ok_or_else(|| format!("missing {}", what))
+    }
+
+    fn unexpected(pair: &Pair<Rule>) -> String {
+        format!(
+            "unexpected {:?} `{}` at position {}",
+            pair.as_rule(),
+            pair.as_str(),
+            pair.as_span().start()
+        )
+    }
+
+    fn var(name: Option<String>, kind: Kind) -> Var {
+        Var {
+            file: None,
+            line_number: None,
+            name,
+            kind,
+            desc: None,
+            visibility: Visibility::Public,
+        }
+    }
+
+    fn function(name: Option<String>, params: Vec<Var>, returns: Vec<Var>) -> Kind {
+        Kind::Function(Function {
+            file: None,
+            line_number: None,
+            name,
+            params,
+            returns,
+            desc: None,
+            visibility: Visibility::Public,
+        })
+    }
+
+    fn named_var(pair: Pair<Rule>) -> Result<Var, String> {
         let mut inner = pair.into_inner();
-        let name = Some(Self::as_string(&inner.next().unwrap()));
-        let next = inner.next().unwrap();
+        let name = Some(Self::as_string(&Self::next(&mut inner, "variable name")?));
+        let next = Self::next(&mut inner, "variable type")?;
         match next.as_rule() {
-            Rule::nullable_tail => Var {
-                file: None,
-                line_number: None,
-                name,
-                kind: Kind::Nullable(Box::new(Self::kind(inner.next().unwrap()))),
-                desc: None,
-            },
-            _ => Var {
-                file: None,
-                line_number: None,
-                name,
-                kind: Self::kind(next),
-                desc: None,
-            },
+            Rule::nullable_tail => {
+                let kind = Self::kind(Self::next(&mut inner, "variable type")?)?;
+                Ok(Self::var(name, Kind::Nullable(Box::new(kind))))
+            }
+            _ => Ok(Self::var(name, Self::kind(next)?)),
         }
     }
 
-    fn returns(pair: Option<Pair<Rule>>) -> Vec<Var> {
+    fn returns(pair: Option<Pair<Rule>>) -> Result<Vec<Var>, String> {
         let mut returns: Vec<Var> = vec![];
         if let Some(pair) = pair {
             for p in pair.into_inner() {
-                returns.push(match p.as_rule() {
-                    Rule::named_var => Self::named_var(p),
-                    Rule::enumeration | Rule::complex_type => Var {
-                        file: None,
-                        line_number: None,
-                        kind: Self::kind(p),
-                        name: None,
-                        desc: None,
-                    },
-                    _ => unreachable!(),
-                })
+                match p.as_rule() {
+                    Rule::named_var => returns.push(Self::named_var(p)?),
+                    Rule::enumeration | Rule::complex_type => {
+                        returns.push(Self::var(None, Self::kind(p)?))
+                    }
+                    // optional return values: `fun(): (integer, string)?`
+                    Rule::nullable_tail => {
+                        for ret in returns.iter_mut() {
+                            ret.kind = Kind::Nullable(Box::new(ret.kind.clone()));
+                        }
+                    }
+                    _ => return Err(Self::unexpected(&p)),
+                }
             }
         }
-        returns
+        Ok(returns)
     }
 
-    fn args(pair: Pair<Rule>) -> Vec<Var> {
+    fn args(pair: Pair<Rule>) -> Result<Vec<Var>, String> {
         let mut params: Vec<Var> = vec![];
         for arg in pair.into_inner() {
             match arg.as_rule() {
-                Rule::named_var => params.push(Self::named_var(arg)),
-                Rule::valid_name => params.push(Var {
-                    file: None,
-                    line_number: None,
-                    name: Some(Self::as_string(&arg)),
-                    kind: Kind::Lua(LuaKind::Any),
-                    desc: None,
-                }),
+                Rule::named_var => params.push(Self::named_var(arg)?),
+                Rule::valid_name => params.push(Self::var(
+                    Some(Self::as_string(&arg)),
+                    Kind::Lua(LuaKind::Any),
+                )),
                 Rule::nullable_tail => {
                     if let Some(last) = params.last_mut() {
                         last.kind = Kind::Nullable(Box::new(last.kind.clone()))
                     }
                 }
-                Rule::vararg => params.push(Var {
-                    file: None,
-                    line_number: None,
-                    name: None,
-                    kind: Kind::Variadic(Box::new(Self::kind(arg.into_inner().next().unwrap()))),
-                    desc: None,
-                }),
-                _ => unreachable!(),
+                Rule::vararg => {
+                    let kind = match arg.into_inner().next() {
+                        Some(kind) => Self::kind(kind)?,
+                        None => Kind::Lua(LuaKind::Any),
+                    };
+                    params.push(Self::var(None, Kind::Variadic(Box::new(kind))))
+                }
+                _ => return Err(Self::unexpected(&arg)),
             };
         }
-        params
+        Ok(params)
     }
 
-    fn kind(pair: Pair<Rule>) -> Kind {
+    fn kind(pair: Pair<Rule>) -> Result<Kind, String> {
         let s = Self::as_string(&pair);
-        match pair.as_rule() {
+        let kind = match pair.as_rule() {
             Rule::id => Kind::Unresolved(s),
             Rule::string_literal => Kind::Literal(
                 Box::new(LuaKind::String),
-                Self::as_string(&pair.into_inner().next().unwrap()),
+                Self::as_string(&Self::next(&mut pair.into_inner(), "string")?),
             ),
-            Rule::number_literal => Kind::Literal(Box::new(LuaKind::Integer), s),
+            Rule::number_literal => {
+                let digits = s.trim_start_matches('-');
+                let is_hex = digits.starts_with("0x") || digits.starts_with("0X");
+                if !is_hex && digits.contains(['.', 'e', 'E']) {
+                    Kind::Literal(Box::new(LuaKind::Number), s)
+                } else {
+                    Kind::Literal(Box::new(LuaKind::Integer), s)
+                }
+            }
+            Rule::boolean_literal => Kind::Literal(Box::new(LuaKind::Boolean), s),
             Rule::lua_type => {
                 match serde_json::from_str::<LuaKind>(
                     format!("\"{}\"", pair.as_span().as_str()).as_str(),
@@ -122,7 +159,7 @@ impl LuaParser {
             }
             Rule::complex_type => {
                 let mut inner = pair.into_inner();
-                let mut k = Self::kind(inner.next().unwrap());
+                let mut k = Self::kind(Self::next(&mut inner, "type")?)?;
                 for tail in inner {
                     match tail.as_rule() {
                         Rule::array_tail => k = Kind::Array(Box::new(k)),
@@ -132,81 +169,71 @@ impl LuaParser {
                 }
                 k
             }
-            Rule::enumeration => Kind::Enum(pair.into_inner().map(|p| Self::kind(p)).collect()),
+            Rule::enumeration => Kind::Enum(
+                pair.into_inner()
+                    .map(|p| Self::kind(p))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Rule::tuple => Kind::Tuple(
+                pair.into_inner()
+                    .map(|p| Self::kind(p))
+                    .collect::<Result<_, _>>()?,
+            ),
             Rule::table_type => {
                 let mut inner = pair.into_inner();
-                let key = inner.next().unwrap();
-                let value = inner.next().unwrap();
-                Kind::Table(Box::new(Self::kind(key)), Box::new(Self::kind(value)))
+                let key = Self::kind(Self::next(&mut inner, "table key type")?)?;
+                let value = Self::kind(Self::next(&mut inner, "table value type")?)?;
+                Kind::Table(Box::new(key), Box::new(value))
             }
             Rule::object => {
-                let inner = pair.into_inner();
-                let mut fields = HashMap::new();
-                for f in inner {
+                let mut fields = vec![];
+                for f in pair.into_inner() {
                     let mut fi = f.into_inner();
-                    let key = fi.next().unwrap();
+                    let key = Self::next(&mut fi, "object key")?;
+                    let key = match key.as_rule() {
+                        Rule::literal_key => ObjectKey::Literal(Self::as_string(&Self::next(
+                            &mut key.into_inner(),
+                            "object key",
+                        )?)),
+                        Rule::index_key => {
+                            let text = Self::as_string(&key);
+                            match Self::kind(Self::next(&mut key.into_inner(), "object key")?)? {
+                                Kind::Lua(lua_kind) => ObjectKey::Index(lua_kind),
+                                _ => ObjectKey::Name(text),
+                            }
+                        }
+                        _ => ObjectKey::Name(Self::as_string(&key)),
+                    };
                     if let Some(t) = fi.next() {
-                        fields.insert(
-                            key.as_span().as_str().to_string(),
-                            Box::new(Self::kind(t.clone())),
-                        );
+                        fields.push((key, Self::kind(t)?));
                     }
                 }
                 Kind::Object(fields)
             }
             Rule::function => {
                 let mut inner = pair.into_inner();
-                let file = None;
-                let line_number = None;
-                let name = Some(Self::as_string(&inner.next().unwrap()));
-                let params = Self::args(inner.next().unwrap());
-                let returns = Self::returns(inner.next());
-                Kind::Function(Function {
-                    file,
-                    line_number,
-                    name,
-                    params,
-                    returns,
-                    desc: None,
-                })
+                let name = Some(Self::as_string(&Self::next(&mut inner, "function name")?));
+                let params = Self::args(Self::next(&mut inner, "function arguments")?)?;
+                let returns = Self::returns(inner.next())?;
+                Self::function(name, params, returns)
             }
             Rule::fun => {
                 let mut inner = pair.into_inner();
-                let file = None;
-                let line_number = None;
-                let params = Self::args(inner.next().unwrap());
-                let returns = Self::returns(inner.next());
-                Kind::Function(Function {
-                    file,
-                    line_number,
-                    params,
-                    returns,
-                    name: None,
-                    desc: None,
-                })
+                let params = Self::args(Self::next(&mut inner, "function arguments")?)?;
+                let returns = Self::returns(inner.next())?;
+                Self::function(None, params, returns)
             }
             Rule::method => {
                 let mut inner = pair.into_inner();
-                let _parent = Self::as_string(&inner.next().unwrap());
-                let file = None;
-                let line_number = None;
-                let name = Some(Self::as_string(&inner.next().unwrap()));
-                let params = Self::args(inner.next().unwrap());
-                let returns = Self::returns(inner.next());
-                Kind::Function(Function {
-                    file,
-                    line_number,
-                    name,
-                    params,
-                    returns,
-                    desc: None,
-                })
-            }
-            _ => {
-                println!("{:?}", pair.as_rule());
-                unreachable!()
+                let _parent = Self::as_string(&Self::next(&mut inner, "method class")?);
+                let name = Some(Self::as_string(&Self::next(&mut inner, "method name")?));
+                let params = Self::args(Self::next(&mut inner, "method arguments")?)?;
+                let returns = Self::returns(inner.next())?;
+                Self::function(name, params, returns)
             }
-        }
+            _ => return Err(Self::unexpected(&pair)),
+        };
+        Ok(kind)
     }
 }
 
@@ -217,18 +244,14 @@ mod test {
     use super::*;
 
     fn assert_type(input: &str, k: Kind) -> Result<(), String> {
-        assert_eq!(LuaParser::type_def(input), k);
+        assert_eq!(LuaParser::type_def(input)?, k);
         Ok(())
     }
 
-    fn var<N: Into<Option<String>>>(name: N, kind: Kind) -> Var {
-        let name = name.into();
-        Var {
-            file: None,
-            line_number: None,
-            name,
-            kind,
-            desc: None,
+    fn assert_error(input: &str) -> Result<(), String> {
+        match LuaParser::type_def(input) {
+            Ok(kind) => Err(format!("expected `{}` to fail, got {:?}", input, kind)),
+            Err(_) => Ok(()),
         }
     }
 
@@ -273,11 +296,12 @@ mod test {
                 line_number: None,
                 name: Some(String::from("a.b.c")),
                 params: vec![
-                    var("i".to_string(), Kind::Lua(LuaKind::Integer)),
-                    var(None, Kind::Variadic(Box::new(Kind::Lua(LuaKind::String)))),
+                    Var::new("i", Kind::Lua(LuaKind::Integer)),
+                    Var::unnamed(Kind::Variadic(Box::new(Kind::Lua(LuaKind::String)))),
                 ],
                 returns: vec![],
                 desc: None,
+                visibility: Visibility::Public,
             }),
         )?;
         assert_type(
@@ -287,9 +311,9 @@ mod test {
                 line_number: None,
                 name: Some(String::from("test")),
                 params: vec![
-                    var("a".to_string(), Kind::Lua(LuaKind::Integer)),
-                    var(
-                        "b".to_string(),
+                    Var::new("a", Kind::Lua(LuaKind::Integer)),
+                    Var::new(
+                        "b",
                         Kind::Enum(vec![
                             Kind::Lua(LuaKind::Integer),
                             Kind::Nullable(Box::new(Kind::Lua(LuaKind::String))),
@@ -298,8 +322,139 @@ mod test {
                 ],
                 returns: vec![],
                 desc: None,
+                visibility: Visibility::Public,
+            }),
+        )?;
+        assert_type(
+            "{ x: number, y: number, width: number }",
+            Kind::Object(vec![
+                (ObjectKey::Name("x".to_string()), Kind::Lua(LuaKind::Number)),
+                (ObjectKey::Name("y".to_string()), Kind::Lua(LuaKind::Number)),
+                (
+                    ObjectKey::Name("width".to_string()),
+                    Kind::Lua(LuaKind::Number),
+                ),
+            ]),
+        )?;
+        assert_type(
+            "{ [1]: string, [string]: any, [integer]: boolean }",
+            Kind::Object(vec![
+                (
+                    ObjectKey::Literal("1".to_string()),
+                    Kind::Lua(LuaKind::String),
+                ),
+                (ObjectKey::Index(LuaKind::String), Kind::Lua(LuaKind::Any)),
+                (
+                    ObjectKey::Index(LuaKind::Integer),
+                    Kind::Lua(LuaKind::Boolean),
+                ),
+            ]),
+        )?;
+        assert_type("-1", Kind::Literal(Box::new(LuaKind::Integer), "-1".into()))?;
+        assert_type(
+            "0x10",
+            Kind::Literal(Box::new(LuaKind::Integer), "0x10".into()),
+        )?;
+        assert_type(
+            "0.5",
+            Kind::Literal(Box::new(LuaKind::Number), "0.5".into()),
+        )?;
+        assert_type(
+            "1e-3",
+            Kind::Literal(Box::new(LuaKind::Number), "1e-3".into()),
+        )?;
+        assert_type(
+            "true|false",
+            Kind::Enum(vec![
+                Kind::Literal(Box::new(LuaKind::Boolean), "true".into()),
+                Kind::Literal(Box::new(LuaKind::Boolean), "false".into()),
+            ]),
+        )?;
+        assert_type("truely", Kind::Unresolved("truely".into()))?;
+        assert_type(
+            "'a'|`b`",
+            Kind::Enum(vec![string_literal("a"), string_literal("b")]),
+        )?;
+        assert_type(
+            "[number, string?][]",
+            Kind::Array(Box::new(Kind::Tuple(vec![
+                Kind::Lua(LuaKind::Number),
+                Kind::Nullable(Box::new(Kind::Lua(LuaKind::String))),
+            ]))),
+        )?;
+        assert_type(
+            "table<string,integer>",
+            Kind::Table(
+                Box::new(Kind::Lua(LuaKind::String)),
+                Box::new(Kind::Lua(LuaKind::Integer)),
+            ),
+        )?;
+        assert_type(
+            "table<string|integer, thread>",
+            Kind::Table(
+                Box::new(Kind::Enum(vec![
+                    Kind::Lua(LuaKind::String),
+                    Kind::Lua(LuaKind::Integer),
+                ])),
+                Box::new(Kind::Lua(LuaKind::Thread)),
+            ),
+        )?;
+        assert_type("lightuserdata", Kind::Lua(LuaKind::LightUserData))?;
+        assert_type("thread_pool", Kind::Unresolved("thread_pool".into()))?;
+        assert_type(
+            "binary[]",
+            Kind::Array(Box::new(Kind::Lua(LuaKind::Binary))),
+        )?;
+        assert_type("binary_data", Kind::Unresolved("binary_data".into()))?;
+        assert_type(
+            "async fun( a: integer, ...: string )",
+            Kind::Function(Function {
+                file: None,
+                line_number: None,
+                name: None,
+                params: vec![
+                    Var::new("a", Kind::Lua(LuaKind::Integer)),
+                    Var::unnamed(Kind::Variadic(Box::new(Kind::Lua(LuaKind::String)))),
+                ],
+                returns: vec![],
+                desc: None,
+                visibility: Visibility::Public,
+            }),
+        )?;
+        assert_type(
+            "fun(...): (integer, string)?",
+            Kind::Function(Function {
+                file: None,
+                line_number: None,
+                name: None,
+                params: vec![Var::unnamed(Kind::Variadic(Box::new(Kind::Lua(
+                    LuaKind::Any,
+                ))))],
+                returns: vec![
+                    Var::unnamed(Kind::Nullable(Box::new(Kind::Lua(LuaKind::Integer)))),
+                    Var::unnamed(Kind::Nullable(Box::new(Kind::Lua(LuaKind::String)))),
+                ],
+                desc: None,
+                visibility: Visibility::Public,
+            }),
+        )?;
+        assert_type(
+            "fun(): (integer|string)[]",
+            Kind::Function(Function {
+                file: None,
+                line_number: None,
+                name: None,
+                params: vec![],
+                returns: vec![Var::unnamed(Kind::Array(Box::new(Kind::Enum(vec![
+                    Kind::Lua(LuaKind::Integer),
+                    Kind::Lua(LuaKind::String),
+                ]))))],
+                desc: None,
+                visibility: Visibility::Public,
             }),
         )?;
+        assert_error("table<string>")?;
+        assert_error("fun(a: integer")?;
         Ok(())
     }
 }
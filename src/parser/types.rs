@@ -4,6 +4,7 @@ use std::{
     path::PathBuf,
 };
 
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 // -------------------------------------------------------------------------------------------------
@@ -37,12 +38,13 @@ pub enum Kind {
     Array(Box<Kind>),
     Nullable(Box<Kind>),
     Table(Box<Kind>, Box<Kind>),
-    Object(HashMap<String, Box<Kind>>),
-    Alias(Box<Alias>),
-    Class(Class),
+    Object(Vec<(ObjectKey, Kind)>),
+    Alias(SymbolId),
+    Class(SymbolId),
     Function(Function),
     Enum(Vec<Kind>),
-    EnumRef(Box<Enum>),
+    EnumRef(SymbolId),
+    Tuple(Vec<Kind>),
     SelfArg,
     Variadic(Box<Kind>),
     Literal(Box<LuaKind>, String),
@@ -50,6 +52,131 @@ pub enum Kind {
 
 // -------------------------------------------------------------------------------------------------
 
+/// key of a field in an inline object type such as `{ x: number, [string]: any }`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjectKey {
+    /// a named field, e.g. `x`
+    Name(String),
+    /// a literal index key, e.g. `[1]`
+    Literal(String),
+    /// an index signature, e.g. `[string]` or `[integer]`
+    Index(LuaKind),
+}
+
+impl fmt::Display for ObjectKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => write!(f, "{}", name),
+            Self::Literal(value) => write!(f, "[{}]", value),
+            Self::Index(kind) => write!(f, "[{}]", kind.show()),
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// handle of a class, alias or enum in a [`SymbolTable`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SymbolId(usize);
+
+/// the definition type a symbol refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolType {
+    Class,
+    Alias,
+    Enum,
+}
+
+/// lightweight info about a class, alias or enum, which is needed to link and collect types
+/// without accessing the definition itself
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    pub symbol_type: SymbolType,
+    pub name: String,
+    pub file: Option<PathBuf>,
+    pub scope: Scope,
+}
+
+/// interned classes, aliases and enums of a library, referenced by resolved kinds via SymbolIds
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+    ids: HashMap<String, SymbolId>,
+}
+
+impl SymbolTable {
+    /// add a new symbol. when a symbol with the same name already exists, the existing one
+    /// is kept and its id is returned.
+    pub fn insert(&mut self, symbol: Symbol) -> SymbolId {
+        if let Some(id) = self.ids.get(&symbol.name) {
+            return *id;
+        }
+        let id = SymbolId(self.symbols.len());
+        self.ids.insert(symbol.name.clone(), id);
+        self.symbols.push(symbol);
+        id
+    }
+
+    pub fn id(&self, name: &str) -> Option<SymbolId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn get(&self, id: SymbolId) -> &Symbol {
+        &self.symbols[id.0]
+    }
+
+    pub fn name(&self, id: SymbolId) -> &str {
+        &self.get(id).name
+    }
+
+    pub fn resolve_string(&self, s: &str) -> Option<Kind> {
+        self.id(s).map(|id| match self.get(id).symbol_type {
+            SymbolType::Class => Kind::Class(id),
+            SymbolType::Alias => Kind::Alias(id),
+            SymbolType::Enum => Kind::EnumRef(id),
+        })
+    }
+
+    // cross-reference parsed Kinds as existing classes, enums and aliases
+    pub fn resolve_kind(&self, kind: &Kind) -> Kind {
+        match kind {
+            Kind::Unresolved(s) => self.resolve_string(s).unwrap_or(kind.clone()),
+            Kind::Array(bk) => Kind::Array(Box::new(self.resolve_kind(bk.as_ref()))),
+            Kind::Nullable(bk) => Kind::Nullable(Box::new(self.resolve_kind(bk.as_ref()))),
+            Kind::Table(key, value) => Kind::Table(
+                Box::new(self.resolve_kind(key.as_ref())),
+                Box::new(self.resolve_kind(value.as_ref())),
+            ),
+            Kind::Enum(kinds) => Kind::Enum(kinds.iter().map(|k| self.resolve_kind(k)).collect()),
+            Kind::Tuple(kinds) => Kind::Tuple(kinds.iter().map(|k| self.resolve_kind(k)).collect()),
+            Kind::Function(f) => {
+                let mut fun = f.clone();
+                self.resolve_function(&mut fun);
+                Kind::Function(fun)
+            }
+            Kind::Variadic(v) => Kind::Variadic(Box::new(self.resolve_kind(v.as_ref()))),
+            Kind::Object(fields) => Kind::Object(
+                fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), self.resolve_kind(value)))
+                    .collect(),
+            ),
+            _ => kind.clone(),
+        }
+    }
+
+    pub fn resolve_function(&self, f: &mut Function) {
+        for p in f.params.iter_mut() {
+            p.kind = self.resolve_kind(&p.kind)
+        }
+        for r in f.returns.iter_mut() {
+            r.kind = self.resolve_kind(&r.kind)
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 /// a definition alias, rendered as a doc page
 #[derive(Debug, Clone, PartialEq)]
 pub struct Alias {
@@ -61,100 +188,147 @@ pub struct Alias {
 }
 
 impl Kind {
-    #[allow(unused)]
-    pub fn collect_local_class_types(&self) -> HashSet<String> {
+    pub fn collect_local_class_types(&self, symbols: &SymbolTable) -> HashSet<String> {
         let mut types = HashSet::new();
         match self {
             Kind::Unresolved(_) => {}
             Kind::Lua(_lua_kind) => {}
             Kind::Array(item) => {
-                types.extend(item.collect_local_class_types());
+                types.extend(item.collect_local_class_types(symbols));
             }
             Kind::Nullable(item) => {
-                types.extend(item.collect_local_class_types());
+                types.extend(item.collect_local_class_types(symbols));
             }
             Kind::Table(key, value) => {
-                types.extend(key.collect_local_class_types());
-                types.extend(value.collect_local_class_types());
+                types.extend(key.collect_local_class_types(symbols));
+                types.extend(value.collect_local_class_types(symbols));
             }
-            Kind::Object(map) => {
-                for kind in map.values() {
-                    types.extend(kind.collect_local_class_types());
+            Kind::Object(fields) => {
+                for (_, kind) in fields {
+                    types.extend(kind.collect_local_class_types(symbols));
                 }
             }
-            Kind::Alias(alias) => {}
-            Kind::Class(class) => {
+            Kind::Alias(_) => {}
+            Kind::Class(id) => {
+                let class = symbols.get(*id);
                 if class.scope == Scope::Local {
                     types.insert(class.name.clone());
                 }
-                types.extend(class.collect_local_class_types());
             }
             Kind::Function(func) => {
                 for ret in &func.returns {
-                    types.extend(ret.kind.collect_local_class_types());
+                    types.extend(ret.kind.collect_local_class_types(symbols));
                 }
                 for param in &func.params {
-                    types.extend(param.kind.collect_local_class_types());
+                    types.extend(param.kind.collect_local_class_types(symbols));
                 }
             }
-            Kind::Enum(kinds) => {
+            Kind::Enum(kinds) | Kind::Tuple(kinds) => {
                 for kind in kinds {
-                    types.extend(kind.collect_local_class_types());
+                    types.extend(kind.collect_local_class_types(symbols));
                 }
             }
             Kind::EnumRef(_) => {}
             Kind::SelfArg => {}
             Kind::Variadic(item) => {
-                types.extend(item.collect_local_class_types());
+                types.extend(item.collect_local_class_types(symbols));
+            }
+            Kind::Literal(_lua_kind, _) => {}
+        }
+        types
+    }
+
+    pub fn collect_builtin_types(&self) -> HashSet<String> {
+        let mut types = HashSet::new();
+        match self {
+            Kind::Unresolved(_) => {}
+            Kind::Lua(lua_kind) => {
+                types.insert(lua_kind.show());
+            }
+            Kind::Array(item) => {
+                types.extend(item.collect_builtin_types());
+            }
+            Kind::Nullable(item) => {
+                types.insert(LuaKind::Nil.show());
+                types.extend(item.collect_builtin_types());
+            }
+            Kind::Table(key, value) => {
+                types.extend(key.collect_builtin_types());
+                types.extend(value.collect_builtin_types());
+            }
+            Kind::Object(fields) => {
+                for (_, kind) in fields {
+                    types.extend(kind.collect_builtin_types());
+                }
+            }
+            Kind::Alias(_) => {}
+            Kind::Class(_) => {}
+            Kind::Function(func) => {
+                for ret in &func.returns {
+                    types.extend(ret.kind.collect_builtin_types());
+                }
+                for param in &func.params {
+                    types.extend(param.kind.collect_builtin_types());
+                }
+            }
+            Kind::Enum(kinds) | Kind::Tuple(kinds) => {
+                for kind in kinds {
+                    types.extend(kind.collect_builtin_types());
+                }
+            }
+            Kind::EnumRef(_) => {}
+            Kind::SelfArg => {
+                types.insert("self".to_string());
+            }
+            Kind::Variadic(item) => {
+                types.extend(item.collect_builtin_types());
             }
             Kind::Literal(_lua_kind, _) => {}
         }
         types
     }
 
-    pub fn collect_alias_types(&self) -> HashSet<String> {
+    pub fn collect_alias_types(&self, symbols: &SymbolTable) -> HashSet<String> {
         let mut types = HashSet::new();
         match self {
             Kind::Unresolved(_name) => {}
             Kind::Lua(_lua_kind) => {}
             Kind::Array(kind) => {
-                types.extend(kind.collect_alias_types());
+                types.extend(kind.collect_alias_types(symbols));
             }
             Kind::Nullable(item) => {
-                types.extend(item.collect_alias_types());
+                types.extend(item.collect_alias_types(symbols));
             }
             Kind::Table(key, value) => {
-                types.extend(key.collect_alias_types());
-                types.extend(value.collect_alias_types());
+                types.extend(key.collect_alias_types(symbols));
+                types.extend(value.collect_alias_types(symbols));
             }
-            Kind::Object(map) => {
-                for kind in map.values() {
-                    types.extend(kind.collect_alias_types());
+            Kind::Object(fields) => {
+                for (_, kind) in fields {
+                    types.extend(kind.collect_alias_types(symbols));
                 }
             }
-            Kind::Alias(alias) => {
-                types.insert(alias.name.clone());
-            }
-            Kind::Class(class) => {
-                types.extend(class.collect_alias_types());
+            Kind::Alias(id) => {
+                types.insert(symbols.name(*id).to_string());
             }
+            Kind::Class(_) => {}
             Kind::Function(function) => {
                 for ret in &function.returns {
-                    types.extend(ret.kind.collect_alias_types());
+                    types.extend(ret.kind.collect_alias_types(symbols));
                 }
                 for param in &function.params {
-                    types.extend(param.kind.collect_alias_types());
+                    types.extend(param.kind.collect_alias_types(symbols));
                 }
             }
-            Kind::Enum(kinds) => {
+            Kind::Enum(kinds) | Kind::Tuple(kinds) => {
                 for kind in kinds {
-                    types.extend(kind.collect_alias_types());
+                    types.extend(kind.collect_alias_types(symbols));
                 }
             }
             Kind::EnumRef(_enumref) => {}
             Kind::SelfArg => {}
             Kind::Variadic(item) => {
-                types.extend(item.collect_alias_types());
+                types.extend(item.collect_alias_types(symbols));
             }
             Kind::Literal(_lua_kind, _) => {}
         }
@@ -164,6 +338,33 @@ impl Kind {
 
 // -------------------------------------------------------------------------------------------------
 
+/// visibility of class members, as annotated via `@private`, `@protected` or `@package`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Visibility {
+    #[default]
+    Public,
+    Protected,
+    Package,
+    Private,
+}
+
+impl Visibility {
+    pub fn is_public(&self) -> bool {
+        *self == Visibility::Public
+    }
+
+    pub fn show(&self) -> &'static str {
+        match self {
+            Visibility::Public => "public",
+            Visibility::Protected => "protected",
+            Visibility::Package => "package",
+            Visibility::Private => "private",
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 /// variable definition used in fields and params and returns of functions
 #[derive(Debug, Clone, PartialEq)]
 pub struct Var {
@@ -172,19 +373,20 @@ pub struct Var {
     pub name: Option<String>,
     pub kind: Kind,
     pub desc: Option<String>,
+    pub visibility: Visibility,
     // pub default: String,
     // pub range: String
 }
 
 impl Var {
-    pub fn is_constant(&self) -> bool {
-        self.name
-            .as_ref()
-            .is_some_and(|name| name.chars().all(|c| c.is_uppercase() || c == '_'))
-    }
-
-    pub fn is_not_constant(&self) -> bool {
-        !self.is_constant()
+    pub fn strip_base(&self) -> Self {
+        Self {
+            name: self
+                .name
+                .as_ref()
+                .map(|name| Class::get_end(name).unwrap_or(name).to_string()),
+            ..self.clone()
+        }
     }
 }
 
@@ -199,6 +401,7 @@ pub struct Function {
     pub params: Vec<Var>,
     pub returns: Vec<Var>,
     pub desc: Option<String>,
+    pub visibility: Visibility,
     // pub overloads: ?
 }
 
@@ -244,6 +447,51 @@ impl Enum {
 
 // -------------------------------------------------------------------------------------------------
 
+/// operator metamethod of a class, as defined via `---@operator name(operand): result`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operator {
+    pub file: Option<PathBuf>,
+    pub line_number: Option<u32>,
+    pub name: String,
+    pub operand: Option<Kind>,
+    pub result: Option<Kind>,
+    pub desc: Option<String>,
+}
+
+impl Operator {
+    /// the Lua syntax of the operator, e.g. `+` for `add`
+    pub fn symbol(&self) -> &str {
+        match self.name.as_str() {
+            "add" => "+",
+            "sub" | "unm" => "-",
+            "mul" => "*",
+            "div" => "/",
+            "idiv" => "//",
+            "mod" => "%",
+            "pow" => "^",
+            "concat" => "..",
+            "len" => "#",
+            "band" => "&",
+            "bor" => "|",
+            "bxor" | "bnot" => "~",
+            "shl" => "<<",
+            "shr" => ">>",
+            "eq" => "==",
+            "lt" => "<",
+            "le" => "<=",
+            "call" => "()",
+            name => name,
+        }
+    }
+
+    /// kinds of the operand and result
+    pub fn kinds(&self) -> impl Iterator<Item = &Kind> {
+        self.operand.iter().chain(self.result.iter())
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
 /// scope of a class item
 #[derive(Debug, Clone, PartialEq)]
 pub enum Scope {
@@ -251,25 +499,27 @@ pub enum Scope {
     Local,
     Builtins,
     Modules,
+    /// the table which gets returned by a module file, loaded via `require("name")`
+    Require(String),
 }
 
 impl Scope {
-    pub fn from_name(name: &str, namespace: &str) -> Self {
-        const LUA_STD_LIBS: [&str; 8] = ["bit", "debug", "io", "math", "os", "table", "jit", "ffi"];
-        if namespace.is_empty() {
+    pub fn from_name(name: &str, namespaces: &[String], std_modules: &[String]) -> Self {
+        let is_std_module = std_modules.iter().any(|m| m == name);
+        if namespaces.is_empty() {
             // all global classes are treated as, well, globals
             if name == "global" {
                 Scope::Global
-            } else if LUA_STD_LIBS.contains(&name) {
+            } else if is_std_module {
                 Scope::Modules
             } else {
                 Scope::Local
             }
         } else {
-            // only classes that belong to the root namespace are treated as global classes
-            if Class::belongs_to_namespace(name, namespace) {
+            // only classes that belong to a root namespace are treated as global classes
+            if Class::belongs_to_namespace(name, namespaces) {
                 Scope::Global
-            } else if LUA_STD_LIBS.contains(&name) || name == "global" {
+            } else if is_std_module || name == "global" {
                 Scope::Modules
             } else {
                 Scope::Local
@@ -277,17 +527,20 @@ impl Scope {
         }
     }
 
-    pub fn path_prefix(&self, namespace: &str) -> String {
+    pub fn path_prefix(&self) -> String {
         match self {
-            Scope::Global | Scope::Local => {
-                if namespace.is_empty() {
-                    "API/".to_string()
-                } else {
-                    format!("API/{}/", namespace)
-                }
-            }
+            Scope::Global | Scope::Local => "API/".to_string(),
             Scope::Builtins => "API/builtins/".to_string(),
             Scope::Modules => "API/modules/".to_string(),
+            Scope::Require(_) => "API/require/".to_string(),
+        }
+    }
+
+    /// file name of the doc page of a class with the given name in this scope
+    pub fn page_name(&self, class_name: &str) -> String {
+        match self {
+            Scope::Require(module) => module.clone(),
+            _ => class_name.to_string(),
         }
     }
 }
@@ -305,16 +558,28 @@ pub struct Class {
     pub functions: Vec<Function>,
     pub enums: Vec<Enum>,
     pub constants: Vec<Var>,
+    pub operators: Vec<Operator>,
+    /// functions which create instances by calling the class table or a function named like
+    /// the class, e.g. `acme.SomeClass()`
+    pub constructors: Vec<Function>,
+    /// names of the classes whose constructors create instances of this class, see
+    /// [`Class::constructed_classes`]
+    pub constructed_by: Vec<String>,
     pub desc: String,
 }
 
 impl Class {
-    pub fn belongs_to_namespace(name: &str, namespace: &str) -> bool {
-        if !namespace.is_empty() {
-            name == namespace || name.starts_with(&(namespace.to_string() + "."))
-        } else {
-            false
-        }
+    pub fn belongs_to_namespace(name: &str, namespaces: &[String]) -> bool {
+        namespaces.iter().any(|namespace| {
+            !namespace.is_empty()
+                && (name == namespace || name.starts_with(&(namespace.to_string() + ".")))
+        })
+    }
+
+    /// doc page path of a namespace class, relative to the API folder. Nested namespace
+    /// tables are mapped to sub directories, e.g. `acme/sub/Thing` for `acme.sub.Thing`.
+    pub fn page_path(name: &str) -> String {
+        name.replace('.', "/")
     }
 
     pub fn get_base(name: &str) -> Option<&str> {
@@ -325,127 +590,174 @@ impl Class {
         name.rfind('.').map(|pos| &name[pos + 1..])
     }
 
-    pub fn collect_local_types(
-        &self,
-        structs: &HashMap<String, Class>,
-        aliases: &HashMap<String, Alias>,
-    ) -> (HashSet<String>, HashSet<String>) {
-        let mut local_class_names = self.collect_local_class_types();
-        let mut local_alias_names = self.collect_alias_types();
-
-        // loop until recursion settled
-        loop {
-            let mut new_local_class_names = local_class_names.clone();
-            let mut new_local_alias_names = local_alias_names.clone();
-
-            // find local structs and aliases names in aliases
-            for name in new_local_alias_names.clone() {
-                let alias = aliases.get(&name).unwrap();
-                new_local_class_names.extend(alias.kind.collect_local_class_types());
-                new_local_alias_names.extend(alias.kind.collect_alias_types());
-            }
-
-            // find alias and local struct names in local structs
-            for name in new_local_class_names.clone() {
-                let struct_ = structs.get(&name).unwrap();
-                new_local_alias_names.extend(struct_.collect_alias_types());
-                new_local_class_names.extend(struct_.collect_local_class_types());
-            }
-
-            // resolve new structs and aliases
-            for alias in new_local_alias_names.clone().into_iter() {
-                if let Some(alias) = aliases.get(&alias) {
-                    if let Kind::Alias(alias) = &alias.kind {
-                        new_local_alias_names.insert(alias.name.clone());
-                    } else if let Kind::Class(class) = &alias.kind {
-                        if class.scope == Scope::Local {
-                            new_local_class_names.insert(class.name.to_string());
-                        }
-                        new_local_alias_names.extend(class.collect_alias_types());
-                        new_local_class_names.extend(class.collect_local_class_types());
-                    }
-                }
-            }
-
-            if new_local_class_names != local_class_names
-                || new_local_alias_names != local_alias_names
-            {
-                local_class_names.clone_from(&new_local_class_names);
-                local_alias_names.clone_from(&new_local_alias_names);
-            } else {
-                break;
-            }
+    /// merge members of another definition of the same class into this class. the description
+    /// of the other class is used when this class has none. the merged class is located at
+    /// the definition which comes first in file order.
+    pub fn merge(&mut self, other: Class) {
+        let location = |class: &Class| (class.file.clone(), class.line_number);
+        if other.file.is_some() && (self.file.is_none() || location(&other) < location(self)) {
+            self.file = other.file.clone();
+            self.line_number = other.line_number;
+        }
+        self.fields.extend(other.fields);
+        self.functions.extend(other.functions);
+        self.enums.extend(other.enums);
+        self.constants.extend(other.constants);
+        self.operators.extend(other.operators);
+        self.constructors.extend(other.constructors);
+        if self.desc.is_empty() {
+            self.desc = other.desc;
         }
-
-        (local_class_names, local_alias_names)
     }
 
-    pub fn collect_local_aliases(&self, aliases: &HashMap<String, Alias>) -> HashSet<String> {
-        let mut local_alias_names = self.collect_alias_types();
+    /// all files which define this class or some of its members. the file of the class comes
+    /// first, when it's known.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let member_files = self
+            .fields
+            .iter()
+            .chain(&self.constants)
+            .map(|v| &v.file)
+            .chain(
+                self.functions
+                    .iter()
+                    .chain(&self.constructors)
+                    .map(|f| &f.file),
+            )
+            .chain(self.enums.iter().map(|e| &e.file))
+            .chain(self.operators.iter().map(|o| &o.file))
+            .flatten()
+            .filter(|file| self.file.as_ref() != Some(*file))
+            .unique()
+            .sorted()
+            .cloned();
+        self.file.iter().cloned().chain(member_files).collect()
+    }
 
-        // loop until recursion settled
-        loop {
-            let mut new_local_alias_names = local_alias_names.clone();
+    /// a copy of this class with the members which are defined in the given file only
+    pub fn partial(&self, file: &PathBuf) -> Class {
+        let in_file = |f: &Option<PathBuf>| f.as_ref() == Some(file);
+        Class {
+            file: Some(file.clone()),
+            fields: self
+                .fields
+                .iter()
+                .filter(|v| in_file(&v.file))
+                .cloned()
+                .collect(),
+            functions: self
+                .functions
+                .iter()
+                .filter(|f| in_file(&f.file))
+                .cloned()
+                .collect(),
+            enums: self
+                .enums
+                .iter()
+                .filter(|e| in_file(&e.file))
+                .cloned()
+                .collect(),
+            constants: self
+                .constants
+                .iter()
+                .filter(|v| in_file(&v.file))
+                .cloned()
+                .collect(),
+            operators: self
+                .operators
+                .iter()
+                .filter(|o| in_file(&o.file))
+                .cloned()
+                .collect(),
+            constructors: self
+                .constructors
+                .iter()
+                .filter(|f| in_file(&f.file))
+                .cloned()
+                .collect(),
+            desc: String::new(),
+            ..self.clone()
+        }
+    }
 
-            // find aliases names in aliases
-            for name in new_local_alias_names.clone() {
-                let alias = aliases.get(&name).unwrap();
-                new_local_alias_names.extend(alias.kind.collect_alias_types());
-            }
+    /// names of all classes the constructors of this class return instances of
+    pub fn constructed_classes(&self, symbols: &SymbolTable) -> HashSet<String> {
+        self.constructors
+            .iter()
+            .flat_map(|f| &f.returns)
+            .filter_map(|r| match r.kind {
+                Kind::Class(id) => Some(symbols.name(id).to_string()),
+                _ => None,
+            })
+            .collect()
+    }
 
-            // resolve new aliases
-            for alias in new_local_alias_names.clone().into_iter() {
-                if let Some(alias) = aliases.get(&alias) {
-                    if let Kind::Alias(alias) = &alias.kind {
-                        new_local_alias_names.insert(alias.name.clone());
-                    }
-                }
+    pub fn collect_local_class_types(&self, symbols: &SymbolTable) -> HashSet<String> {
+        let mut types = HashSet::new();
+        for field in &self.fields {
+            types.extend(field.kind.collect_local_class_types(symbols));
+        }
+        for function in self.functions.iter().chain(&self.constructors) {
+            for ret in &function.returns {
+                types.extend(ret.kind.collect_local_class_types(symbols));
             }
-
-            if new_local_alias_names != local_alias_names {
-                local_alias_names.clone_from(&new_local_alias_names);
-            } else {
-                break;
+            for param in &function.params {
+                types.extend(param.kind.collect_local_class_types(symbols));
             }
         }
-
-        local_alias_names
+        for con in &self.constants {
+            types.extend(con.kind.collect_local_class_types(symbols));
+        }
+        for kind in self.operators.iter().flat_map(Operator::kinds) {
+            types.extend(kind.collect_local_class_types(symbols));
+        }
+        types
     }
 
-    pub fn collect_local_class_types(&self) -> HashSet<String> {
+    pub fn collect_builtin_types(&self) -> HashSet<String> {
         let mut types = HashSet::new();
         for field in &self.fields {
-            types.extend(field.kind.collect_local_class_types());
+            types.extend(field.kind.collect_builtin_types());
         }
-        for function in &self.functions {
+        for function in self.functions.iter().chain(&self.constructors) {
             for ret in &function.returns {
-                types.extend(ret.kind.collect_local_class_types());
+                types.extend(ret.kind.collect_builtin_types());
             }
             for param in &function.params {
-                types.extend(param.kind.collect_local_class_types());
+                types.extend(param.kind.collect_builtin_types());
             }
         }
         for con in &self.constants {
-            types.extend(con.kind.collect_local_class_types());
+            types.extend(con.kind.collect_builtin_types());
+        }
+        for kind in self.operators.iter().flat_map(Operator::kinds) {
+            types.extend(kind.collect_builtin_types());
+        }
+        if !self.operators.is_empty() {
+            // operators are rendered as expressions with self
+            types.insert("self".to_string());
         }
         types
     }
 
-    pub fn collect_alias_types(&self) -> HashSet<String> {
+    pub fn collect_alias_types(&self, symbols: &SymbolTable) -> HashSet<String> {
         let mut types = HashSet::new();
         for field in &self.fields {
-            types.extend(field.kind.collect_alias_types());
+            types.extend(field.kind.collect_alias_types(symbols));
         }
-        for function in &self.functions {
+        for function in self.functions.iter().chain(&self.constructors) {
             for ret in &function.returns {
-                types.extend(ret.kind.collect_alias_types());
+                types.extend(ret.kind.collect_alias_types(symbols));
             }
             for param in &function.params {
-                types.extend(param.kind.collect_alias_types());
+                types.extend(param.kind.collect_alias_types(symbols));
             }
         }
         for con in &self.constants {
-            types.extend(con.kind.collect_alias_types());
+            types.extend(con.kind.collect_alias_types(symbols));
+        }
+        for kind in self.operators.iter().flat_map(Operator::kinds) {
+            types.extend(kind.collect_alias_types(symbols));
         }
         types
     }
@@ -460,6 +772,35 @@ pub enum Def {
     Enum(Enum),
     Alias(Alias),
     Function(Function),
+    Var(Var),
+}
+
+// -------------------------------------------------------------------------------------------------
+
+/// a type which failed to parse, with the location and symbol path of its definition
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub file: Option<PathBuf>,
+    pub line_number: Option<u32>,
+    pub symbol: String,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file = self
+            .file
+            .as_ref()
+            .map(|file| file.to_string_lossy())
+            .unwrap_or("[unknown file]".into());
+        let file = file.strip_prefix("file://").unwrap_or(&file);
+        match self.line_number {
+            // LuaLS positions are encoded as `line * 10000 + column`, starting at 0
+            Some(position) => write!(f, "{}:{}", file, position / 10000 + 1)?,
+            None => write!(f, "{}", file)?,
+        }
+        write!(f, " in {}: {}", self.symbol, self.message)
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -536,11 +877,20 @@ impl fmt::Display for Kind {
                     .collect::<Vec<String>>()
                     .join(", ")
             ),
-            Self::Object(hm) => {
+            Self::Tuple(ks) => write!(
+                f,
+                "Tuple({})",
+                ks.iter()
+                    .map(|k| format!("{}", k))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Self::Object(fields) => {
                 write!(
                     f,
                     "Object({})",
-                    hm.iter()
+                    fields
+                        .iter()
                         .map(|(k, v)| format!("{} : {}", k, v))
                         .collect::<Vec<String>>()
                         .join(", ")
@@ -598,7 +948,7 @@ impl Class {
                 return true;
             }
         }
-        for f in &self.functions {
+        for f in self.functions.iter().chain(&self.constructors) {
             if f.has_unresolved() {
                 return true;
             }
@@ -607,7 +957,11 @@ impl Class {
     }
 
     pub fn is_empty(&self) -> bool {
-        self.fields.is_empty() && self.enums.is_empty() && self.functions.is_empty()
+        self.fields.is_empty()
+            && self.enums.is_empty()
+            && self.functions.is_empty()
+            && self.operators.is_empty()
+            && self.constructors.is_empty()
     }
 
     fn with_new_line(s: &str) -> String {
@@ -649,3 +1003,71 @@ impl Class {
         )
     }
 }
+
+// -------------------------------------------------------------------------------------------------
+
+// test constructors for definitions without a description and source location
+
+#[cfg(test)]
+impl Var {
+    pub(crate) fn new(name: &str, kind: Kind) -> Self {
+        Self {
+            name: Some(name.to_string()),
+            ..Self::unnamed(kind)
+        }
+    }
+
+    pub(crate) fn unnamed(kind: Kind) -> Self {
+        Self {
+            file: None,
+            line_number: None,
+            name: None,
+            kind,
+            desc: None,
+            visibility: Visibility::Public,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Function {
+    pub(crate) fn new(name: &str) -> Self {
+        Self {
+            name: Some(name.to_string()),
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(test)]
+impl Class {
+    pub(crate) fn new(name: &str, scope: Scope) -> Self {
+        Self {
+            file: None,
+            line_number: None,
+            scope,
+            name: name.to_string(),
+            fields: vec![],
+            functions: vec![],
+            enums: vec![],
+            constants: vec![],
+            operators: vec![],
+            constructors: vec![],
+            constructed_by: vec![],
+            desc: String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Alias {
+    pub(crate) fn new(name: &str, kind: Kind) -> Self {
+        Self {
+            file: None,
+            line_number: None,
+            name: name.to_string(),
+            kind,
+            desc: None,
+        }
+    }
+}